@@ -0,0 +1,356 @@
+//! Error-detection capability of CRC-32C for a given message length.
+//!
+//! A message of `n` bytes and its CRC form a codeword of `8 * n + 32` bits. An error pattern
+//! (the bits flipped in the codeword) goes undetected exactly when it is itself divisible by the
+//! CRC polynomial. The CRC-32C polynomial is `(x + 1)` times a primitive polynomial of degree 31,
+//! so every error flipping an odd number of bits is detected, and the powers of `x` only repeat
+//! after `2^31 - 1` bits.
+//!
+//! ```rust
+//! use crc32c::analysis;
+//!
+//! // Every error of up to 5 bits is detected in a 512-byte record...
+//! assert_eq!(analysis::hamming_distance(512), 6);
+//!
+//! // ...but not in a 4-KiB one.
+//! assert_eq!(analysis::hamming_distance(4096), 4);
+//! ```
+
+use crate::crc32c_raw_update;
+use crate::poly::Poly32;
+
+/// Order of `x` modulo the CRC-32C polynomial, in bits.
+const ORDER: u64 = (1 << 31) - 1;
+
+/// Longest message (in bytes) with a given Hamming distance, from Philip Koopman's tables of
+/// CRC polynomials (<https://users.ece.cmu.edu/~koopman/crc/>), where CRC-32C is `0x8f6e37a0`.
+const HAMMING_DISTANCES: [(usize, u32); 6] = [
+    (1, 14),
+    (2, 12),
+    (5, 10),
+    (22, 8),
+    (655, 6),
+    (268_435_451, 4),
+];
+
+/// Longest message (in bytes) for which [undetected_errors] counts the errors of weight 4.
+const MAX_WEIGHT4_LEN: usize = 64 << 10;
+
+/// Returns the Hamming distance of CRC-32C for messages of `message_len` bytes.
+///
+/// Every error flipping fewer bits than this (in the message or the CRC) is guaranteed to be
+/// detected. Returns [u32::MAX] for an empty message, where no error goes undetected.
+pub fn hamming_distance(message_len: usize) -> u32 {
+    if message_len == 0 {
+        return u32::MAX;
+    }
+
+    HAMMING_DISTANCES
+        .iter()
+        .find(|&&(max_len, _)| message_len <= max_len)
+        .map_or(2, |&(_, distance)| distance)
+}
+
+/// Counts the error patterns flipping exactly `weight` bits which go undetected for messages of
+/// `message_len` bytes.
+///
+/// The count is exact for every weight below the Hamming distance, for odd weights, and for
+/// weights 2 and 4. Returns [None] for the other weights, whose count is too expensive to compute.
+///
+/// Counting weight 4 takes time and memory proportional to the message length (about a second
+/// for 4 KiB in release builds), so it is only done for messages of up to 64 KiB, and [None] is
+/// returned for longer ones.
+pub fn undetected_errors(message_len: usize, weight: u32) -> Option<u64> {
+    let bits = 8 * message_len as u64 + 32;
+
+    if weight % 2 == 1 || weight < hamming_distance(message_len) {
+        return Some(0);
+    }
+
+    match weight {
+        // Two bits `k * ORDER` apart.
+        2 => Some((1..=bits / ORDER).map(|k| bits - k * ORDER).sum()),
+        4 if message_len <= MAX_WEIGHT4_LEN => Some(weight4::count(bits)),
+        _ => None,
+    }
+}
+
+/// Returns whether an error is detected in a message of `message_len` bytes.
+///
+/// The error is XOR-ed into the codeword (the message followed by its CRC in little-endian order,
+/// as written by most formats) starting at byte `offset`. A burst spanning 32 bits or less is
+/// always detected.
+///
+/// # Panics
+///
+/// Panics if the error extends past the end of the codeword.
+pub fn is_detected(message_len: usize, offset: usize, error: &[u8]) -> bool {
+    let trailing = (message_len + 4)
+        .checked_sub(offset + error.len())
+        .expect("error pattern extends past the end of the codeword");
+
+    // CRC of the error pattern with a zero seed and no final inversion.
+    let crc = crc32c_raw_update(0, error);
+    let shifted = Poly32::x_pow(8 * trailing as u64) * Poly32::from_register(crc);
+
+    shifted != Poly32::ZERO
+}
+
+/// Counts the undetected errors of weight 4.
+///
+/// Since every error of even weight is divisible by `x + 1`, an error `x^a + x^b + x^c + x^d` is
+/// undetected when it is divisible by the degree 31 factor `Q`, i.e. `x^a + x^b = x^c + x^d` in
+/// the field `GF(2)[x] / Q`. There, `x^i + x^(i + g) = x^(i + Z(g))`, where the Zech logarithm
+/// `Z(g)` is the discrete logarithm of `1 + x^g`. The pairs with gap `g` thus cover the interval
+/// of exponents `[Z(g), Z(g) + bits - g)`, and the undetected errors are the overlaps between
+/// those intervals (each error being found once for each of its 3 splits into two pairs).
+mod weight4 {
+    use super::ORDER;
+
+    /// The degree 31 factor of the CRC-32C polynomial, in the natural bit order (bit i holds
+    /// x^i, including x^31).
+    const Q: u32 = 0xF5_B4_25_3F;
+
+    /// Multiplies by `x` in `GF(2)[x] / Q`.
+    fn times_x(a: u32) -> u32 {
+        let a = a << 1;
+
+        if a & (1 << 31) == 0 {
+            a
+        } else {
+            a ^ Q
+        }
+    }
+
+    fn multiply(mut a: u32, mut b: u32) -> u32 {
+        let mut p = 0;
+
+        while b != 0 {
+            if b & 1 != 0 {
+                p ^= a;
+            }
+            b >>= 1;
+            a = times_x(a);
+        }
+
+        p
+    }
+
+    fn pow(a: u32, mut n: u64) -> u32 {
+        let mut result = 1;
+        let mut square = a;
+
+        while n != 0 {
+            if n & 1 != 0 {
+                result = multiply(result, square);
+            }
+            square = multiply(square, square);
+            n >>= 1;
+        }
+
+        result
+    }
+
+    /// Discrete logarithms base `x`, using the baby-step giant-step algorithm.
+    struct Log {
+        steps: u64,
+        bits: u32,
+        keys: Vec<u32>,
+        values: Vec<u32>,
+        giant: [[u32; 256]; 4],
+    }
+
+    impl Log {
+        fn new(steps: u64) -> Self {
+            let size = 2 * steps as usize;
+            let mut log = Self {
+                steps,
+                bits: size.trailing_zeros(),
+                keys: vec![0; size],
+                values: vec![0; size],
+                giant: [[0; 256]; 4],
+            };
+
+            // Baby steps: a hash table from x^j to j.
+            let mut p = 1;
+            for j in 0..steps as u32 {
+                let mut slot = log.slot(p);
+                while log.keys[slot] != 0 {
+                    slot = (slot + 1) % size;
+                }
+                log.keys[slot] = p;
+                log.values[slot] = j;
+                p = times_x(p);
+            }
+
+            // Giant steps multiply by x^-steps, which is linear.
+            let inverse = pow(2, ORDER - steps);
+            for (i, row) in log.giant.iter_mut().enumerate() {
+                for (n, entry) in row.iter_mut().enumerate() {
+                    *entry = multiply(inverse, (n as u32) << (i * 8));
+                }
+            }
+
+            log
+        }
+
+        fn slot(&self, p: u32) -> usize {
+            (p.wrapping_mul(0x9E_37_79_B1) >> (32 - self.bits)) as usize
+        }
+
+        fn find(&self, p: u32) -> Option<u32> {
+            let mut slot = self.slot(p);
+
+            while self.keys[slot] != 0 {
+                if self.keys[slot] == p {
+                    return Some(self.values[slot]);
+                }
+                slot = (slot + 1) % self.keys.len();
+            }
+
+            None
+        }
+
+        fn log(&self, mut p: u32) -> u64 {
+            let mut i = 0;
+
+            loop {
+                if let Some(j) = self.find(p) {
+                    return (i * self.steps + u64::from(j)) % ORDER;
+                }
+
+                p = self.giant[0][p as u8 as usize]
+                    ^ self.giant[1][(p >> 8) as u8 as usize]
+                    ^ self.giant[2][(p >> 16) as u8 as usize]
+                    ^ self.giant[3][(p >> 24) as u8 as usize];
+                i += 1;
+            }
+        }
+    }
+
+    pub(super) fn count(bits: u64) -> u64 {
+        let steps = ((bits as f64 * ORDER as f64 / 2.0).sqrt() as u64)
+            .next_power_of_two()
+            .clamp(1 << 10, 1 << 20);
+        let log = Log::new(steps);
+
+        // Zech logarithms. Squaring is linear in GF(2), so Z(2g) = 2 Z(g).
+        let mut zech = vec![0; bits as usize];
+        let mut p = 1;
+        for g in 1..bits as usize {
+            p = times_x(p);
+            zech[g] = if g % 2 == 0 {
+                2 * zech[g / 2] % ORDER
+            } else {
+                log.log(p ^ 1)
+            };
+        }
+
+        let mut events = Vec::with_capacity(4 * bits as usize);
+        for (g, &start) in zech.iter().enumerate().skip(1) {
+            let end = start + bits - g as u64;
+
+            if end <= ORDER {
+                events.extend_from_slice(&[(start, 1), (end, -1)]);
+            } else {
+                events.extend_from_slice(&[(start, 1), (ORDER, -1), (0, 1), (end - ORDER, -1)]);
+            }
+        }
+        events.sort_unstable();
+
+        // Each exponent covered by `n` intervals is shared by `n * (n - 1) / 2` pairs of pairs.
+        let mut overlaps = 0;
+        let mut covering: i64 = 0;
+        let mut last = 0;
+
+        for (at, delta) in events {
+            let n = covering as u64;
+            overlaps += (at - last) * (n * n.saturating_sub(1) / 2);
+            last = at;
+            covering += delta;
+        }
+
+        overlaps / 3
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::poly::POLYNOMIAL;
+
+        #[test]
+        fn factor() {
+            // (x + 1) * Q is the CRC-32C polynomial, in the natural bit order.
+            let product = (u64::from(Q) << 1) ^ u64::from(Q);
+            assert_eq!(product, (1 << 32) | u64::from(POLYNOMIAL.reverse_bits()));
+
+            // x is primitive.
+            assert_eq!(pow(2, ORDER), 1);
+        }
+
+        #[test]
+        fn can_count() {
+            // Compared with a brute-force search over all pairs of bits.
+            assert_eq!(count(5275), 0);
+            assert_eq!(count(5276), 1);
+            assert_eq!(count(5400), 322);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, RngCore};
+
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(hamming_distance(0), u32::MAX);
+        assert_eq!(hamming_distance(1), 14);
+        assert_eq!(hamming_distance(655), 6);
+        assert_eq!(hamming_distance(656), 4);
+        assert_eq!(hamming_distance(268_435_451), 4);
+        assert_eq!(hamming_distance(268_435_452), 2);
+    }
+
+    #[test]
+    fn undetected() {
+        assert_eq!(undetected_errors(655, 4), Some(0));
+        assert_eq!(undetected_errors(656, 3), Some(0));
+        assert!(undetected_errors(656, 4).unwrap() > 0);
+        assert_eq!(undetected_errors(656, 6), None);
+        assert_eq!(undetected_errors(MAX_WEIGHT4_LEN + 1, 4), None);
+        assert_eq!(undetected_errors(268_435_451, 4), None);
+
+        // The codeword is exactly 2^31 bits, one more than the order of x.
+        assert_eq!(undetected_errors(268_435_452, 2), Some(1));
+    }
+
+    #[test]
+    fn bursts() {
+        let mut rng = rand::rng();
+
+        for _ in 0..100 {
+            let mut error = [0u8; 4];
+            rng.fill_bytes(&mut error);
+            error[0] |= 1;
+
+            let offset = rng.random_range(0..=1000);
+            assert!(is_detected(1000, offset, &error));
+        }
+    }
+
+    #[test]
+    fn undetected_pattern() {
+        // A message followed by its unseeded CRC is itself a codeword.
+        let mut error = vec![0u8; 100];
+        rand::rng().fill_bytes(&mut error);
+        let crc = crc32c_raw_update(0, &error);
+        error.extend_from_slice(&crc.to_le_bytes());
+
+        assert!(!is_detected(100, 0, &error));
+        assert!(!is_detected(200, 50, &error));
+        assert!(is_detected(200, 50, &error[..100]));
+    }
+}
@@ -0,0 +1,315 @@
+//! Assembles the CRC-32C of an object from segments received in any order.
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::{error, fmt};
+
+use crate::Crc32c;
+
+/// Error returned by [Crc32cAssembler::add] when a segment cannot be placed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentError {
+    /// The segment overlaps bytes already covered by `covered`.
+    Overlap {
+        /// The byte range of the segment.
+        segment: Range<u64>,
+        /// The range already covered, which the segment overlaps.
+        covered: Range<u64>,
+    },
+    /// The segment extends past the total length of the object.
+    PastEnd {
+        /// The byte range of the segment.
+        segment: Range<u64>,
+        /// The total length of the object.
+        len: u64,
+    },
+    /// The end of the segment does not fit in a `u64`.
+    Overflow {
+        /// The offset of the segment.
+        offset: u64,
+        /// The length of the segment.
+        len: u64,
+    },
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SegmentError::Overlap { segment, covered } => write!(
+                f,
+                "segment {segment:?} overlaps the already covered range {covered:?}"
+            ),
+            SegmentError::PastEnd { segment, len } => write!(
+                f,
+                "segment {segment:?} extends past the end of the object ({len} bytes)"
+            ),
+            SegmentError::Overflow { offset, len } => write!(
+                f,
+                "segment of {len} bytes at offset {offset} ends past the largest offset"
+            ),
+        }
+    }
+}
+
+impl error::Error for SegmentError {}
+
+/// Computes the CRC-32C of an object from the checksums of its segments, added in any order.
+///
+/// Adjacent segments are merged with [crc32c_combine](crate::crc32c_combine) as soon as they are
+/// added, so only the checksums of the disjoint covered ranges are kept. The assembler can be
+/// shared between threads (e.g. download workers fetching different ranges of the object).
+///
+/// # Example
+///
+/// ```rust
+/// use crc32c::Crc32cAssembler;
+///
+/// let assembler = Crc32cAssembler::new();
+///
+/// assembler.add(6, crc32c::crc32c(b"world!"), 6).unwrap();
+/// assert_eq!(assembler.gaps(), vec![0..6]);
+/// assert_eq!(assembler.crc32c(), None);
+///
+/// assembler.add(0, crc32c::crc32c(b"Hello "), 6).unwrap();
+/// assert_eq!(assembler.crc32c(), Some(0x7B_98_E7_51));
+/// ```
+#[derive(Debug, Default)]
+pub struct Crc32cAssembler {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The covered ranges, by offset. Adjacent ranges are always merged.
+    segments: BTreeMap<u64, Crc32c>,
+    /// The total length of the object, if known.
+    len: Option<u64>,
+}
+
+impl Crc32cAssembler {
+    /// Create an assembler for an object of unknown length.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an assembler for an object of `len` bytes.
+    ///
+    /// Segments past the end are rejected, and the CRC is only available once all `len` bytes
+    /// are covered.
+    pub fn with_len(len: u64) -> Self {
+        Self {
+            state: Mutex::new(State {
+                segments: BTreeMap::new(),
+                len: Some(len),
+            }),
+        }
+    }
+
+    /// Add the checksum `crc` of the `len` bytes starting at `offset`.
+    ///
+    /// Empty segments are ignored. On error, the assembler is left unchanged.
+    pub fn add(&self, offset: u64, crc: u32, len: u64) -> Result<(), SegmentError> {
+        let end = offset
+            .checked_add(len)
+            .ok_or(SegmentError::Overflow { offset, len })?;
+        let mut state = self.state();
+
+        if let Some(total) = state.len {
+            if end > total {
+                return Err(SegmentError::PastEnd {
+                    segment: offset..end,
+                    len: total,
+                });
+            }
+        }
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        let before = state.segments.range(..end).next_back();
+        if let Some((&start, digest)) = before {
+            if start + digest.len() > offset {
+                return Err(SegmentError::Overlap {
+                    segment: offset..end,
+                    covered: start..start + digest.len(),
+                });
+            }
+        }
+
+        let mut start = offset;
+        let mut digest = Crc32c::from_parts(crc, len);
+
+        if let Some((&prev, prev_digest)) = before {
+            if prev + prev_digest.len() == offset {
+                start = prev;
+                digest = *prev_digest + digest;
+            }
+        }
+
+        if let Some(next) = state.segments.remove(&end) {
+            digest += next;
+        }
+
+        state.segments.remove(&start);
+        state.segments.insert(start, digest);
+
+        Ok(())
+    }
+
+    /// Returns the byte ranges not covered yet, in order.
+    ///
+    /// When the total length is unknown, only the gaps before the last covered byte are
+    /// reported.
+    pub fn gaps(&self) -> Vec<Range<u64>> {
+        let state = self.state();
+        let mut gaps = Vec::new();
+        let mut covered = 0;
+
+        for (&start, digest) in &state.segments {
+            if start > covered {
+                gaps.push(covered..start);
+            }
+            covered = start + digest.len();
+        }
+
+        if let Some(len) = state.len {
+            if len > covered {
+                gaps.push(covered..len);
+            }
+        }
+
+        gaps
+    }
+
+    /// Returns the CRC of the object, once the covered bytes are contiguous from the start.
+    ///
+    /// When the total length is known, every byte must be covered. Otherwise, the object is
+    /// assumed to end with the last covered byte, and [None] is returned while nothing is
+    /// covered.
+    pub fn crc32c(&self) -> Option<u32> {
+        let state = self.state();
+
+        match (state.segments.len(), state.segments.get(&0), state.len) {
+            (0, _, Some(0)) => Some(0),
+            (1, Some(digest), Some(len)) if digest.len() == len => Some(digest.crc()),
+            (1, Some(digest), None) => Some(digest.crc()),
+            _ => None,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // The state is consistent between statements, so a panic elsewhere cannot corrupt it.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+    use rand::RngCore;
+    use std::thread;
+
+    use super::*;
+    use crate::crc32c;
+
+    #[test]
+    fn can_assemble() {
+        let mut buf = vec![0u8; 10_000];
+        rand::rng().fill_bytes(&mut buf);
+
+        let mut segments: Vec<_> = (0..buf.len()).step_by(700).collect();
+        segments.shuffle(&mut rand::rng());
+
+        let assembler = Crc32cAssembler::with_len(buf.len() as u64);
+        let mut added = 0;
+        for offset in segments {
+            assert_eq!(assembler.crc32c(), None);
+
+            let segment = &buf[offset..buf.len().min(offset + 700)];
+            let len = segment.len() as u64;
+            assert_eq!(assembler.add(offset as u64, crc32c(segment), len), Ok(()));
+            added += len;
+
+            let missing: u64 = assembler.gaps().iter().map(|gap| gap.end - gap.start).sum();
+            assert_eq!(missing, buf.len() as u64 - added);
+        }
+
+        assert_eq!(assembler.gaps(), vec![]);
+        assert_eq!(assembler.crc32c(), Some(crc32c(&buf)));
+    }
+
+    #[test]
+    fn gaps_and_overlaps() {
+        let assembler = Crc32cAssembler::new();
+
+        assembler.add(10, 0, 10).unwrap();
+        assembler.add(30, 0, 5).unwrap();
+        assert_eq!(assembler.gaps(), vec![0..10, 20..30]);
+
+        assert_eq!(
+            assembler.add(15, 0, 10),
+            Err(SegmentError::Overlap {
+                segment: 15..25,
+                covered: 10..20
+            })
+        );
+        assert_eq!(
+            assembler.add(25, 0, 6),
+            Err(SegmentError::Overlap {
+                segment: 25..31,
+                covered: 30..35
+            })
+        );
+        assert!(assembler.add(0, 0, 40).is_err());
+        assert_eq!(
+            assembler.add(u64::MAX, 0, 2),
+            Err(SegmentError::Overflow {
+                offset: u64::MAX,
+                len: 2
+            })
+        );
+        assert_eq!(assembler.gaps(), vec![0..10, 20..30]);
+
+        assembler.add(20, 0, 10).unwrap();
+        assert_eq!(assembler.gaps(), vec![0..10]);
+
+        let bounded = Crc32cAssembler::with_len(10);
+        assert_eq!(bounded.gaps(), vec![0..10]);
+        assert_eq!(
+            bounded.add(5, 0, 6),
+            Err(SegmentError::PastEnd {
+                segment: 5..11,
+                len: 10
+            })
+        );
+
+        assert_eq!(Crc32cAssembler::new().crc32c(), None);
+        assert_eq!(Crc32cAssembler::with_len(0).crc32c(), Some(0));
+    }
+
+    #[test]
+    fn concurrent() {
+        let mut buf = vec![0u8; 100_000];
+        rand::rng().fill_bytes(&mut buf);
+
+        let assembler = Crc32cAssembler::with_len(buf.len() as u64);
+        let chunks: Vec<_> = buf.chunks(1000).enumerate().collect();
+
+        thread::scope(|scope| {
+            for worker in chunks.chunks(10) {
+                let assembler = &assembler;
+                scope.spawn(move || {
+                    for &(i, chunk) in worker.iter().rev() {
+                        let offset = (i * 1000) as u64;
+                        assembler
+                            .add(offset, crc32c(chunk), chunk.len() as u64)
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(assembler.crc32c(), Some(crc32c(&buf)));
+    }
+}
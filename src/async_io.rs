@@ -0,0 +1,569 @@
+//! Provides wrappers for asynchronous readers and writers which checksum the bytes being
+//! read/written, for `tokio` (with the `tokio` feature) and `futures` (with the `futures-io`
+//! feature).
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::io::{append_prefix, Lookahead};
+use crate::{crc32c_append, Crc32c};
+
+/// Asynchronous reader wrapper which tracks the checksum of all bytes read.
+///
+/// Implements `AsyncRead` and `AsyncBufRead` for the traits of the enabled features. With
+/// `AsyncBufRead`, bytes are checksummed as they are consumed.
+pub struct AsyncCrc32cReader<R> {
+    checksum: u32,
+    len: u64,
+    inner: R,
+    lookahead: Lookahead,
+}
+
+impl<R: Unpin> AsyncCrc32cReader<R> {
+    /// Wrap an instance of an asynchronous reader.
+    pub fn new(r: R) -> Self {
+        Self::new_with_seed(r, 0)
+    }
+
+    /// Wrap an asynchronous reader, with the checksum seeded with a particular value.
+    pub fn new_with_seed(r: R, seed: u32) -> Self {
+        Self {
+            checksum: seed,
+            len: 0,
+            inner: r,
+            lookahead: Lookahead::default(),
+        }
+    }
+
+    /// Wrap an asynchronous reader positioned after the bytes covered by `state`, to resume a
+    /// checksum saved with [Crc32c::to_bytes].
+    pub fn resume(r: R, state: Crc32c) -> Self {
+        Self {
+            checksum: state.crc(),
+            len: state.len(),
+            ..Self::new(r)
+        }
+    }
+
+    /// Unwrap the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Get the checksum of all bytes read.
+    pub fn crc32c(&self) -> u32 {
+        self.checksum
+    }
+
+    /// Get the checksum of all bytes read, with their count.
+    ///
+    /// The count includes the bytes read through this wrapper and those covered by the state it
+    /// was resumed from, but not those covered by the seed.
+    pub fn digest(&self) -> Crc32c {
+        Crc32c::from_parts(self.checksum, self.len)
+    }
+
+    fn update(&mut self, buf: &[u8]) {
+        self.checksum = crc32c_append(self.checksum, buf);
+        self.len += buf.len() as u64;
+    }
+
+    fn consumed(&mut self, amt: usize) {
+        let consumed = self.lookahead.consume(amt);
+        self.checksum = crc32c_append(self.checksum, consumed);
+        self.len += consumed.len() as u64;
+    }
+}
+
+/// Records the buffer returned by the inner reader, to checksum it when it is consumed.
+fn filled<'a>(
+    lookahead: &mut Lookahead,
+    poll: Poll<io::Result<&'a [u8]>>,
+) -> Poll<io::Result<&'a [u8]>> {
+    if let Poll::Ready(Ok(buf)) = poll {
+        lookahead.filled(buf);
+    }
+
+    poll
+}
+
+/// Asynchronous writer wrapper which tracks the checksum of all bytes written.
+///
+/// Implements `AsyncWrite` for the traits of the enabled features. Only the bytes accepted by
+/// the inner writer are checksummed, including for partial and vectored writes.
+pub struct AsyncCrc32cWriter<W> {
+    checksum: u32,
+    len: u64,
+    inner: W,
+}
+
+impl<W: Unpin> AsyncCrc32cWriter<W> {
+    /// Wrap an instance of an asynchronous writer.
+    pub fn new(w: W) -> Self {
+        Self::new_with_seed(w, 0)
+    }
+
+    /// Wrap an asynchronous writer, with the checksum seeded with a particular value.
+    pub fn new_with_seed(w: W, seed: u32) -> Self {
+        Self {
+            checksum: seed,
+            len: 0,
+            inner: w,
+        }
+    }
+
+    /// Wrap an asynchronous writer positioned after the bytes covered by `state`, to resume a
+    /// checksum saved with [Crc32c::to_bytes].
+    pub fn resume(w: W, state: Crc32c) -> Self {
+        Self {
+            checksum: state.crc(),
+            len: state.len(),
+            inner: w,
+        }
+    }
+
+    /// Unwrap the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Get the checksum of all bytes written.
+    pub fn crc32c(&self) -> u32 {
+        self.checksum
+    }
+
+    /// Get the checksum of all bytes written, with their count.
+    ///
+    /// The count includes the bytes written through this wrapper and those covered by the state
+    /// it was resumed from, but not those covered by the seed.
+    pub fn digest(&self) -> Crc32c {
+        Crc32c::from_parts(self.checksum, self.len)
+    }
+
+    fn written(&mut self, buf: &[u8], poll: Poll<io::Result<usize>>) -> Poll<io::Result<usize>> {
+        if let Poll::Ready(Ok(n)) = poll {
+            self.checksum = crc32c_append(self.checksum, &buf[..n]);
+            self.len += n as u64;
+        }
+
+        poll
+    }
+
+    fn written_vectored(
+        &mut self,
+        bufs: &[io::IoSlice<'_>],
+        poll: Poll<io::Result<usize>>,
+    ) -> Poll<io::Result<usize>> {
+        if let Poll::Ready(Ok(n)) = poll {
+            self.checksum = append_prefix(self.checksum, bufs.iter().map(|buf| &**buf), n);
+            self.len += n as u64;
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_impl {
+    use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, ReadBuf};
+
+    use super::*;
+
+    impl<R: AsyncRead + Unpin> AsyncRead for AsyncCrc32cReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            let filled = buf.filled().len();
+            this.lookahead.clear();
+
+            let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+            if let Poll::Ready(Ok(())) = poll {
+                this.update(&buf.filled()[filled..]);
+            }
+
+            poll
+        }
+    }
+
+    impl<R: AsyncBufRead + Unpin> AsyncBufRead for AsyncCrc32cReader<R> {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            let this = self.get_mut();
+            filled(
+                &mut this.lookahead,
+                Pin::new(&mut this.inner).poll_fill_buf(cx),
+            )
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            let this = self.get_mut();
+            this.consumed(amt);
+            Pin::new(&mut this.inner).consume(amt);
+        }
+    }
+
+    impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncCrc32cWriter<W> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+            this.written(buf, poll)
+        }
+
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[io::IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+            this.written_vectored(bufs, poll)
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            self.inner.is_write_vectored()
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+mod futures_impl {
+    use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+    use super::*;
+
+    impl<R: AsyncRead + Unpin> AsyncRead for AsyncCrc32cReader<R> {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            this.lookahead.clear();
+
+            let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+            if let Poll::Ready(Ok(n)) = poll {
+                this.update(&buf[..n]);
+            }
+
+            poll
+        }
+
+        fn poll_read_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &mut [io::IoSliceMut<'_>],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            this.lookahead.clear();
+
+            let poll = Pin::new(&mut this.inner).poll_read_vectored(cx, bufs);
+            if let Poll::Ready(Ok(n)) = poll {
+                this.checksum = append_prefix(this.checksum, bufs.iter().map(|buf| &**buf), n);
+                this.len += n as u64;
+            }
+
+            poll
+        }
+    }
+
+    impl<R: AsyncBufRead + Unpin> AsyncBufRead for AsyncCrc32cReader<R> {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            let this = self.get_mut();
+            filled(
+                &mut this.lookahead,
+                Pin::new(&mut this.inner).poll_fill_buf(cx),
+            )
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            let this = self.get_mut();
+            this.consumed(amt);
+            Pin::new(&mut this.inner).consume(amt);
+        }
+    }
+
+    impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncCrc32cWriter<W> {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+            this.written(buf, poll)
+        }
+
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[io::IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+            this.written_vectored(bufs, poll)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_close(cx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::IoSlice;
+
+    use super::*;
+
+    const TEST_STRING: &[u8] =
+        b"This is a very long string which is used to test the CRC-32-Castagnoli function.";
+    const CHECKSUM: u32 = 0x20_CB_1E_59;
+
+    /// A writer which accepts at most 5 bytes per call, across buffers for vectored writes.
+    #[derive(Default)]
+    struct Trickle(Vec<u8>);
+
+    impl Trickle {
+        fn write(&mut self, bufs: &[IoSlice<'_>]) -> Poll<io::Result<usize>> {
+            let mut n = 0;
+
+            for buf in bufs {
+                let len = buf.len().min(5 - n);
+                self.0.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    mod tokio_tests {
+        use tokio::io::{
+            AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+            BufReader, ReadBuf,
+        };
+
+        use super::*;
+
+        impl AsyncWrite for Trickle {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.get_mut().write(&[IoSlice::new(buf)])
+            }
+
+            fn poll_write_vectored(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                bufs: &[IoSlice<'_>],
+            ) -> Poll<io::Result<usize>> {
+                self.get_mut().write(bufs)
+            }
+
+            fn is_write_vectored(&self) -> bool {
+                true
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        #[tokio::test]
+        async fn can_read() {
+            let mut reader = AsyncCrc32cReader::new(TEST_STRING);
+            let mut buf = Vec::new();
+            reader.read_to_end(&mut buf).await.unwrap();
+
+            assert_eq!(buf, TEST_STRING);
+            assert_eq!(reader.crc32c(), CHECKSUM);
+            assert_eq!(reader.digest(), Crc32c::new(TEST_STRING));
+        }
+
+        #[tokio::test]
+        async fn can_buf_read() {
+            let mut reader = AsyncCrc32cReader::new(BufReader::with_capacity(7, TEST_STRING));
+            let mut word = Vec::new();
+
+            reader.read_until(b' ', &mut word).await.unwrap();
+            assert_eq!(word, b"This ");
+            assert_eq!(reader.digest(), Crc32c::new(b"This "));
+
+            reader.read_to_end(&mut word).await.unwrap();
+            assert_eq!(reader.crc32c(), CHECKSUM);
+        }
+
+        /// A buffered reader which fails when its buffer is filled again before being consumed.
+        struct Strict {
+            data: &'static [u8],
+            filled: bool,
+        }
+
+        impl AsyncRead for Strict {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<io::Result<()>> {
+                Pin::new(&mut self.get_mut().data).poll_read(cx, buf)
+            }
+        }
+
+        impl AsyncBufRead for Strict {
+            fn poll_fill_buf(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+                let this = self.get_mut();
+                if this.filled {
+                    return Poll::Ready(Err(io::ErrorKind::WouldBlock.into()));
+                }
+
+                this.filled = true;
+                Poll::Ready(Ok(&this.data[..this.data.len().min(7)]))
+            }
+
+            fn consume(self: Pin<&mut Self>, amt: usize) {
+                let this = self.get_mut();
+                this.filled = false;
+                this.data = &this.data[amt..];
+            }
+        }
+
+        #[tokio::test]
+        async fn consumes_last_buffer() {
+            let inner = Strict {
+                data: TEST_STRING,
+                filled: false,
+            };
+            let mut reader = AsyncCrc32cReader::new(inner);
+
+            let mut word = Vec::new();
+            reader.read_until(b' ', &mut word).await.unwrap();
+            assert_eq!(reader.digest(), Crc32c::new(b"This "));
+
+            reader.read_until(b'!', &mut word).await.unwrap();
+            assert_eq!(reader.crc32c(), CHECKSUM);
+        }
+
+        #[tokio::test]
+        async fn can_write() {
+            let mut writer = AsyncCrc32cWriter::new(Trickle::default());
+            let (a, b) = TEST_STRING.split_at(3);
+
+            let n = writer
+                .write_vectored(&[IoSlice::new(a), IoSlice::new(b)])
+                .await
+                .unwrap();
+            assert_eq!(n, 5);
+            assert_eq!(writer.digest(), Crc32c::new(&TEST_STRING[..5]));
+
+            writer.write_all(&TEST_STRING[5..]).await.unwrap();
+            writer.shutdown().await.unwrap();
+
+            assert_eq!(writer.crc32c(), CHECKSUM);
+            assert_eq!(writer.into_inner().0, TEST_STRING);
+        }
+    }
+
+    #[cfg(feature = "futures-io")]
+    mod futures_tests {
+        use futures::executor::block_on;
+        use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+        use super::*;
+
+        impl AsyncWrite for Trickle {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.get_mut().write(&[IoSlice::new(buf)])
+            }
+
+            fn poll_write_vectored(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                bufs: &[IoSlice<'_>],
+            ) -> Poll<io::Result<usize>> {
+                self.get_mut().write(bufs)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        #[test]
+        fn can_read() {
+            let mut reader = AsyncCrc32cReader::new(TEST_STRING);
+            let mut buf = Vec::new();
+            block_on(reader.read_to_end(&mut buf)).unwrap();
+
+            assert_eq!(buf, TEST_STRING);
+            assert_eq!(reader.crc32c(), CHECKSUM);
+
+            let mut reader = AsyncCrc32cReader::new(TEST_STRING);
+            let (mut a, mut b) = ([0; 3], [0; 10]);
+            let mut bufs = [io::IoSliceMut::new(&mut a), io::IoSliceMut::new(&mut b)];
+            let n = block_on(reader.read_vectored(&mut bufs)).unwrap();
+            assert_eq!(reader.digest(), Crc32c::new(&TEST_STRING[..n]));
+        }
+
+        #[test]
+        fn can_buf_read() {
+            let mut reader = AsyncCrc32cReader::new(BufReader::with_capacity(7, TEST_STRING));
+            let mut word = Vec::new();
+
+            block_on(reader.read_until(b' ', &mut word)).unwrap();
+            assert_eq!(word, b"This ");
+            assert_eq!(reader.digest(), Crc32c::new(b"This "));
+
+            block_on(reader.read_to_end(&mut word)).unwrap();
+            assert_eq!(reader.crc32c(), CHECKSUM);
+        }
+
+        #[test]
+        fn can_write() {
+            let mut writer = AsyncCrc32cWriter::new(Trickle::default());
+            let (a, b) = TEST_STRING.split_at(3);
+
+            let n = block_on(writer.write_vectored(&[IoSlice::new(a), IoSlice::new(b)])).unwrap();
+            assert_eq!(n, 5);
+            assert_eq!(writer.digest(), Crc32c::new(&TEST_STRING[..5]));
+
+            block_on(writer.write_all(&TEST_STRING[5..])).unwrap();
+            block_on(writer.close()).unwrap();
+
+            assert_eq!(writer.crc32c(), CHECKSUM);
+            assert_eq!(writer.into_inner().0, TEST_STRING);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Provides checksumming of [Buf]s, and wrappers for [Buf]s and [BufMut]s which checksum the
+//! bytes being advanced over or put, for the `bytes` crate (with the `bytes` feature).
+use std::io::IoSlice;
+use std::slice;
+
+use bytes::buf::UninitSlice;
+use bytes::{Buf, BufMut};
+
+use crate::{crc32c_append, Crc32c};
+
+/// Computes the CRC for the remaining bytes of a [Buf], without advancing it or copying them.
+///
+/// The chunks are walked with [Buf::chunks_vectored], so chains of buffers (e.g. built with
+/// [Buf::chain]) are checksummed in one pass.
+///
+/// Returns `None` if the buffer does not expose all its remaining bytes this way, as with the
+/// default implementation of [Buf::chunks_vectored], which only exposes the first chunk. Such
+/// buffers can be checksummed by advancing a [Crc32cBuf] over them instead.
+pub fn crc32c_buf<B: Buf + ?Sized>(buf: &B) -> Option<u32> {
+    let mut slices = vec![IoSlice::new(&[]); 16];
+
+    loop {
+        let n = buf.chunks_vectored(&mut slices);
+        let (crc, len) = slices[..n].iter().fold((0, 0), |(crc, len), slice| {
+            (crc32c_append(crc, slice), len + slice.len())
+        });
+
+        if len == buf.remaining() {
+            return Some(crc);
+        }
+
+        if n < slices.len() {
+            return None;
+        }
+
+        slices.resize(2 * n, IoSlice::new(&[]));
+    }
+}
+
+/// [Buf] wrapper which tracks the checksum of all bytes advanced over.
+///
+/// The bytes returned by [Buf::chunk] are checksummed when [Buf::advance] is called, so the
+/// checksum covers exactly the bytes consumed, including with [Buf::copy_to_bytes] or
+/// [Buf::copy_to_slice].
+pub struct Crc32cBuf<B: Buf> {
+    checksum: u32,
+    len: u64,
+    inner: B,
+}
+
+impl<B: Buf> Crc32cBuf<B> {
+    /// Wrap an instance of a [Buf].
+    pub fn new(b: B) -> Self {
+        Self::new_with_seed(b, 0)
+    }
+
+    /// Wrap a [Buf], with the checksum seeded with a particular value.
+    pub fn new_with_seed(b: B, seed: u32) -> Self {
+        Self {
+            checksum: seed,
+            len: 0,
+            inner: b,
+        }
+    }
+
+    /// Wrap a [Buf] positioned after the bytes covered by `state`, to resume a checksum saved
+    /// with [Crc32c::to_bytes].
+    pub fn resume(b: B, state: Crc32c) -> Self {
+        Self {
+            checksum: state.crc(),
+            len: state.len(),
+            inner: b,
+        }
+    }
+
+    /// Unwrap the inner [Buf].
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Get the checksum of all bytes advanced over.
+    pub fn crc32c(&self) -> u32 {
+        self.checksum
+    }
+
+    /// Get the checksum of all bytes advanced over, with their count.
+    ///
+    /// The count includes the bytes consumed through this wrapper and those covered by the state
+    /// it was resumed from, but not those covered by the seed.
+    pub fn digest(&self) -> Crc32c {
+        Crc32c::from_parts(self.checksum, self.len)
+    }
+}
+
+impl<B: Buf> Buf for Crc32cBuf<B> {
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.remaining(), "cannot advance past the end");
+
+        while cnt > 0 {
+            let chunk = self.inner.chunk();
+            let n = chunk.len().min(cnt);
+
+            self.checksum = crc32c_append(self.checksum, &chunk[..n]);
+            self.len += n as u64;
+            self.inner.advance(n);
+            cnt -= n;
+        }
+    }
+}
+
+/// [BufMut] wrapper which tracks the checksum of all bytes put.
+///
+/// The bytes initialized in [BufMut::chunk_mut] are checksummed when [BufMut::advance_mut] is
+/// called, so the checksum covers all the `put_*` methods.
+pub struct Crc32cBufMut<B: BufMut> {
+    checksum: u32,
+    len: u64,
+    inner: B,
+}
+
+impl<B: BufMut> Crc32cBufMut<B> {
+    /// Wrap an instance of a [BufMut].
+    pub fn new(b: B) -> Self {
+        Self::new_with_seed(b, 0)
+    }
+
+    /// Wrap a [BufMut], with the checksum seeded with a particular value.
+    pub fn new_with_seed(b: B, seed: u32) -> Self {
+        Self {
+            checksum: seed,
+            len: 0,
+            inner: b,
+        }
+    }
+
+    /// Wrap a [BufMut] positioned after the bytes covered by `state`, to resume a checksum saved
+    /// with [Crc32c::to_bytes].
+    pub fn resume(b: B, state: Crc32c) -> Self {
+        Self {
+            checksum: state.crc(),
+            len: state.len(),
+            inner: b,
+        }
+    }
+
+    /// Unwrap the inner [BufMut].
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Get the checksum of all bytes put.
+    pub fn crc32c(&self) -> u32 {
+        self.checksum
+    }
+
+    /// Get the checksum of all bytes put, with their count.
+    ///
+    /// The count includes the bytes put through this wrapper and those covered by the state it
+    /// was resumed from, but not those covered by the seed.
+    pub fn digest(&self) -> Crc32c {
+        Crc32c::from_parts(self.checksum, self.len)
+    }
+}
+
+// SAFETY: the bytes are only read after the caller of `advance_mut` initialized them, and the
+// inner buffer is advanced by the same total count.
+unsafe impl<B: BufMut> BufMut for Crc32cBufMut<B> {
+    fn remaining_mut(&self) -> usize {
+        self.inner.remaining_mut()
+    }
+
+    unsafe fn advance_mut(&mut self, mut cnt: usize) {
+        while cnt > 0 {
+            let chunk = self.inner.chunk_mut();
+            let n = chunk.len().min(cnt);
+            assert!(n > 0, "cannot advance past the end");
+
+            // SAFETY: the caller guarantees that the next `cnt` bytes are initialized.
+            let init = unsafe { slice::from_raw_parts(chunk.as_mut_ptr(), n) };
+            self.checksum = crc32c_append(self.checksum, init);
+            self.len += n as u64;
+
+            // SAFETY: as above.
+            unsafe { self.inner.advance_mut(n) };
+            cnt -= n;
+        }
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        self.inner.chunk_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{Bytes, BytesMut};
+
+    use super::*;
+
+    const TEST_STRING: &[u8] =
+        b"This is a very long string which is used to test the CRC-32-Castagnoli function.";
+    const CHECKSUM: u32 = 0x20_CB_1E_59;
+
+    /// Chains every 3-byte chunk of the test string, to span more slices than the first try.
+    fn chain() -> impl Buf {
+        let mut chunks = TEST_STRING.chunks(3).map(Bytes::from_static);
+        let first: Box<dyn Buf> = Box::new(chunks.next().unwrap());
+        chunks.fold(first, |buf, chunk| Box::new(buf.chain(chunk)))
+    }
+
+    /// A buffer exposing its bytes 3 at a time, with the default [Buf::chunks_vectored].
+    struct Fragments(&'static [u8]);
+
+    impl Buf for Fragments {
+        fn remaining(&self) -> usize {
+            self.0.len()
+        }
+
+        fn chunk(&self) -> &[u8] {
+            &self.0[..self.0.len().min(3)]
+        }
+
+        fn advance(&mut self, cnt: usize) {
+            self.0 = &self.0[cnt..];
+        }
+    }
+
+    /// [Fragments] exposing all its chunks through [Buf::chunks_vectored].
+    struct Vectored(Fragments);
+
+    impl Buf for Vectored {
+        fn remaining(&self) -> usize {
+            self.0.remaining()
+        }
+
+        fn chunk(&self) -> &[u8] {
+            self.0.chunk()
+        }
+
+        fn advance(&mut self, cnt: usize) {
+            self.0.advance(cnt);
+        }
+
+        fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+            let chunks = (self.0).0.chunks(3);
+            dst.iter_mut()
+                .zip(chunks)
+                .map(|(slice, chunk)| *slice = IoSlice::new(chunk))
+                .count()
+        }
+    }
+
+    #[test]
+    fn can_checksum_buf() {
+        assert_eq!(crc32c_buf(&TEST_STRING), Some(CHECKSUM));
+        assert_eq!(crc32c_buf(&chain()), Some(CHECKSUM));
+        assert_eq!(
+            crc32c_buf(&Vectored(Fragments(TEST_STRING))),
+            Some(CHECKSUM)
+        );
+
+        let mut buf = chain();
+        buf.advance(10);
+        assert_eq!(crc32c_buf(&buf), Some(crate::crc32c(&TEST_STRING[10..])));
+        assert_eq!(buf.remaining(), TEST_STRING.len() - 10);
+    }
+
+    #[test]
+    fn rejects_hidden_chunks() {
+        assert_eq!(crc32c_buf(&Fragments(TEST_STRING)), None);
+
+        // The first part only exposes its first chunk, so the slices have a gap.
+        let (head, tail) = TEST_STRING.split_at(30);
+        let gapped = Fragments(head).chain(Bytes::from_static(tail));
+        assert_eq!(crc32c_buf(&gapped), None);
+
+        let mut buf = Crc32cBuf::new(gapped);
+        buf.advance(buf.remaining());
+        assert_eq!(buf.crc32c(), CHECKSUM);
+    }
+
+    #[test]
+    fn can_advance() {
+        let mut buf = Crc32cBuf::new(chain());
+
+        let head = buf.copy_to_bytes(10);
+        assert_eq!(head, TEST_STRING[..10]);
+        assert_eq!(buf.digest(), Crc32c::new(&TEST_STRING[..10]));
+
+        buf.advance(buf.remaining());
+        assert_eq!(buf.crc32c(), CHECKSUM);
+    }
+
+    #[test]
+    fn can_put() {
+        let mut buf = Crc32cBufMut::new(BytesMut::new());
+        for chunk in TEST_STRING.chunks(7) {
+            buf.put_slice(chunk);
+        }
+        assert_eq!(buf.crc32c(), CHECKSUM);
+        assert_eq!(buf.into_inner(), TEST_STRING);
+
+        let mut out = [0; 100];
+        let (a, b) = out.split_at_mut(30);
+        let mut buf = Crc32cBufMut::new(a.chain_mut(b));
+        buf.put_slice(TEST_STRING);
+        assert_eq!(buf.digest(), Crc32c::new(TEST_STRING));
+        assert_eq!(&out[..TEST_STRING.len()], TEST_STRING);
+    }
+}
@@ -0,0 +1,300 @@
+//! Splits data into content-defined chunks, using a rolling CRC-32C as the fingerprint.
+use std::io::{self, Read};
+
+use crate::{crc32c, crc32c_append, Crc32cRolling};
+
+/// Size of the rolling window used to find chunk boundaries.
+const WINDOW: usize = 48;
+
+/// Number of bytes scanned for a boundary before they are checksummed, while still in cache.
+const SCAN_BLOCK: usize = 256;
+
+/// A chunk found by a [Chunker].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chunk {
+    /// Offset of the chunk in the data.
+    pub offset: u64,
+    /// Length of the chunk.
+    pub len: usize,
+    /// CRC-32C of the bytes of the chunk.
+    pub crc: u32,
+}
+
+/// Splits data into chunks whose boundaries depend on the content, rather than on the offset.
+///
+/// A boundary is placed after a byte when the rolling CRC of the last 48 bytes has its low bits
+/// clear, so inserting or removing bytes only moves the boundaries close to the edit, and the
+/// other chunks can be deduplicated. Chunks are at least `min` and at most `max` bytes long
+/// (except for the last one, which may be shorter), and about `avg` bytes long on average.
+///
+/// # Example
+///
+/// ```rust
+/// use crc32c::Chunker;
+///
+/// let data = vec![42u8; 100_000];
+/// let chunker = Chunker::new(2048, 8192, 16384);
+///
+/// let mut offset = 0;
+/// for chunk in chunker.chunks(&data) {
+///     assert_eq!(chunk.offset, offset);
+///     assert_eq!(chunk.crc, crc32c::crc32c(&data[offset as usize..][..chunk.len]));
+///     offset += chunk.len as u64;
+/// }
+///
+/// assert_eq!(offset, 100_000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chunker {
+    min: usize,
+    max: usize,
+    mask: u32,
+}
+
+impl Chunker {
+    /// Create a chunker for chunks of `min` to `max` bytes, and about `avg` bytes on average.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `0 < min <= avg <= max`.
+    pub fn new(min: usize, avg: usize, max: usize) -> Self {
+        assert!(
+            0 < min && min <= avg && avg <= max,
+            "chunk sizes must satisfy 0 < min <= avg <= max"
+        );
+
+        // Past `min`, a boundary is found after `2^bits` bytes on average.
+        let bits = (avg - min).max(1).ilog2();
+
+        Self {
+            min,
+            max,
+            mask: (1 << bits) - 1,
+        }
+    }
+
+    /// Split a slice into chunks.
+    pub fn chunks<'a>(&self, data: &'a [u8]) -> SliceChunks<'a> {
+        SliceChunks {
+            chunker: *self,
+            rolling: Crc32cRolling::new(WINDOW),
+            data,
+            offset: 0,
+        }
+    }
+
+    /// Split the bytes of a reader into chunks.
+    ///
+    /// Up to `max` bytes are buffered.
+    pub fn reader<R: Read>(&self, reader: R) -> ReadChunks<R> {
+        ReadChunks {
+            chunker: *self,
+            rolling: Crc32cRolling::new(WINDOW),
+            inner: reader,
+            buf: Vec::with_capacity(self.max),
+            offset: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns the length and the CRC of the chunk at the start of `data`, which holds at most
+    /// `max` bytes.
+    ///
+    /// The CRC is computed in the same pass as the scan for the boundary, one block behind it.
+    fn cut(&self, rolling: &mut Crc32cRolling, data: &[u8]) -> (usize, u32) {
+        if data.len() <= self.min {
+            return (data.len(), crc32c(data));
+        }
+
+        // Bytes before the last window preceding `min` have no influence on the boundaries.
+        let start = self.min.saturating_sub(WINDOW);
+        let mut crc = crc32c(&data[..start]);
+        rolling.reset();
+
+        for (n, block) in data[start..].chunks(SCAN_BLOCK).enumerate() {
+            let offset = start + n * SCAN_BLOCK;
+
+            for (j, &byte) in block.iter().enumerate() {
+                let fingerprint = rolling.push(byte);
+
+                if offset + j + 1 >= self.min && fingerprint & self.mask == 0 {
+                    return (offset + j + 1, crc32c_append(crc, &block[..=j]));
+                }
+            }
+
+            crc = crc32c_append(crc, block);
+        }
+
+        (data.len(), crc)
+    }
+}
+
+/// Iterator over the chunks of a slice, returned by [Chunker::chunks].
+pub struct SliceChunks<'a> {
+    chunker: Chunker,
+    rolling: Crc32cRolling,
+    data: &'a [u8],
+    offset: u64,
+}
+
+impl Iterator for SliceChunks<'_> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let window = &self.data[..self.data.len().min(self.chunker.max)];
+        let (len, crc) = self.chunker.cut(&mut self.rolling, window);
+
+        let chunk = Chunk {
+            offset: self.offset,
+            len,
+            crc,
+        };
+        self.data = &self.data[len..];
+        self.offset += len as u64;
+
+        Some(chunk)
+    }
+}
+
+/// Iterator over the chunks of a reader, returned by [Chunker::reader].
+pub struct ReadChunks<R: Read> {
+    chunker: Chunker,
+    rolling: Crc32cRolling,
+    inner: R,
+    buf: Vec<u8>,
+    offset: u64,
+    eof: bool,
+}
+
+impl<R: Read> ReadChunks<R> {
+    /// Fill the buffer up to `max` bytes, or until the end of the reader.
+    fn fill(&mut self) -> io::Result<()> {
+        while !self.eof && self.buf.len() < self.chunker.max {
+            let len = self.buf.len();
+            self.buf.resize(self.chunker.max, 0);
+
+            match self.inner.read(&mut self.buf[len..]) {
+                Ok(0) => {
+                    self.buf.truncate(len);
+                    self.eof = true;
+                }
+                Ok(n) => self.buf.truncate(len + n),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => self.buf.truncate(len),
+                Err(e) => {
+                    self.buf.truncate(len);
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for ReadChunks<R> {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(e) = self.fill() {
+            return Some(Err(e));
+        }
+
+        if self.buf.is_empty() {
+            return None;
+        }
+
+        let (len, crc) = self.chunker.cut(&mut self.rolling, &self.buf);
+        let chunk = Chunk {
+            offset: self.offset,
+            len,
+            crc,
+        };
+        self.buf.drain(..len);
+        self.offset += len as u64;
+
+        Some(Ok(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    /// A reader which returns a few bytes at a time.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(1000);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    fn random(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        rand::rng().fill_bytes(&mut data);
+        data
+    }
+
+    #[test]
+    fn covers_data() {
+        let data = random(1_000_000);
+        let chunker = Chunker::new(1024, 4096, 16384);
+
+        let chunks: Vec<_> = chunker.chunks(&data).collect();
+        let mut offset = 0;
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.offset, offset as u64);
+            assert!(chunk.len <= 16384);
+            assert!(chunk.len >= 1024 || i == chunks.len() - 1);
+            assert_eq!(chunk.crc, crc32c(&data[offset..offset + chunk.len]));
+            offset += chunk.len;
+        }
+
+        assert_eq!(offset, data.len());
+
+        // Roughly `avg` bytes per chunk.
+        let avg = data.len() / chunks.len();
+        assert!((2048..8192).contains(&avg), "{}", avg);
+    }
+
+    #[test]
+    fn reader_matches_slice() {
+        let data = random(200_000);
+        let chunker = Chunker::new(512, 2048, 8192);
+
+        let from_slice: Vec<_> = chunker.chunks(&data).collect();
+        let from_reader: Vec<_> = chunker
+            .reader(Trickle(&data))
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(from_slice, from_reader);
+        assert_eq!(chunker.chunks(&[]).count(), 0);
+        assert_eq!(chunker.reader(Trickle(&[])).count(), 0);
+    }
+
+    #[test]
+    fn content_defined() {
+        let data = random(200_000);
+        let mut edited = data.clone();
+        edited.splice(1000..1000, *b"inserted");
+
+        let chunker = Chunker::new(512, 2048, 8192);
+        let before: Vec<_> = chunker.chunks(&data).map(|chunk| chunk.crc).collect();
+        let after: Vec<_> = chunker.chunks(&edited).map(|chunk| chunk.crc).collect();
+
+        // Only the chunks around the edit change.
+        let shared = after.iter().filter(|crc| before.contains(crc)).count();
+        assert!(shared + 3 >= before.len(), "{shared} of {}", before.len());
+    }
+}
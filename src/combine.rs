@@ -2,13 +2,13 @@
 //! concatenated together using their individual CRC32c values (plus the length of the second byte
 //! stream).
 //!
-//! This module is essentially a line-by-line translation of ZLIB's CRC "combine" function
-//! implementation from C to Rust, except for the CRC polynomial used (original uses the CRC32
-//! polynomial 0xedb88320UL, we use the CRC32c polynomial 0x82F63B78).
+//! Appending `len2` bytes to the first stream multiplies its CRC by `x^(8 * len2)` modulo the
+//! CRC polynomial, after which the CRC of the second stream is simply added (XOR-ed) in. This is
+//! the same approach as ZLIB's `crc32_combine`.
 //!
-//! Link to original implementation: https://github.com/madler/zlib/blob/master/crc32.c
+//! Link to original implementation: <https://github.com/madler/zlib/blob/master/crc32.c>
 //!
-//! This file is based on the Zlib project, located at: https://github.com/madler/zlib,
+//! This file is based on the Zlib project, located at: <https://github.com/madler/zlib>,
 //! which includes the following notice:
 //!
 //! crc32.c -- compute the CRC-32 of a data stream
@@ -21,80 +21,50 @@
 //! instead of four steps with four exclusive-ors.  This results in about a
 //! factor of two increase in speed on a Power PC G4 (PPC7455) using gcc -O3.
 
-const GF2_DIM: usize = 32;
+use crate::crc32c;
+use crate::poly::Poly32;
 
-fn gf2_matrix_times(mat: &[u32; GF2_DIM], mut vec: u32) -> u32 {
-    let mut sum = 0;
-    let mut idx = 0;
-    while vec > 0 {
-        if vec & 1 == 1 {
-            sum ^= mat[idx];
-        }
-        vec >>= 1;
-        idx += 1;
-    }
-    sum
+/// Returns the operator which shifts a CRC past `len` zero bytes.
+pub(crate) fn zeros_operator(len: u64) -> Poly32 {
+    Poly32::x_pow(len).pow(8)
 }
 
-fn gf2_matrix_square(square: &mut [u32; GF2_DIM], mat: &[u32; GF2_DIM]) {
-    for n in 0..GF2_DIM {
-        square[n] = gf2_matrix_times(mat, mat[n]);
-    }
+/// Appends `len` zero bytes to a CRC, in `O(log(len))` time.
+pub(crate) fn crc32c_append_zeros(crc: u32, len: u64) -> u32 {
+    !(zeros_operator(len) * Poly32::from_register(!crc)).to_register()
 }
 
-pub(crate) fn crc32c_combine(mut crc1: u32, crc2: u32, mut len2: usize) -> u32 {
-    let mut row: u32 = 1;
-    let mut even = [0u32; GF2_DIM]; /* even-power-of-two zeros operator */
-    let mut odd = [0u32; GF2_DIM]; /* odd-power-of-two zeros operator */
-
-    /* degenerate case (also disallow negative lengths) */
+pub(crate) fn crc32c_combine(crc1: u32, crc2: u32, len2: u64) -> u32 {
+    /* degenerate case */
     if len2 == 0 {
         return crc1;
     }
 
-    /* put operator for one zero bit in odd */
-    odd[0] = 0x82F63B78; /* CRC-32c polynomial */
-    #[allow(clippy::needless_range_loop)]
-    for n in 1..GF2_DIM {
-        odd[n] = row;
-        row <<= 1;
-    }
-
-    /* put operator for two zero bits in even */
-    gf2_matrix_square(&mut even, &odd);
+    (zeros_operator(len2) * Poly32::from_register(crc1)).to_register() ^ crc2
+}
 
-    /* put operator for four zero bits in odd */
-    gf2_matrix_square(&mut odd, &even);
+pub(crate) fn crc32c_combine_bits(crc1: u32, crc2: u32, len2: u64) -> u32 {
+    (Poly32::x_pow(len2) * Poly32::from_register(crc1)).to_register() ^ crc2
+}
 
-    /* apply len2 zeros to crc1 (first square will put the operator for one
-    zero byte, eight zero bits, in even) */
-    loop {
-        /* apply zeros operator for this bit of len2 */
-        gf2_matrix_square(&mut even, &odd);
-        if len2 & 1 == 1 {
-            crc1 = gf2_matrix_times(&even, crc1);
-        }
-        len2 >>= 1;
+pub(crate) fn crc32c_append_repeat(crc: u32, pattern: &[u8], mut times: u64) -> u32 {
+    let mut crc = Poly32::from_register(crc);
 
-        /* if no more bits set, then done */
-        if len2 == 0 {
-            break;
-        }
+    // CRC of the pattern repeated 2^k times, and the operator for its length.
+    let mut block = Poly32::from_register(crc32c(pattern));
+    let mut op = zeros_operator(pattern.len() as u64);
 
-        /* another iteration of the loop with odd and even swapped */
-        gf2_matrix_square(&mut odd, &even);
-        if len2 & 1 == 1 {
-            crc1 = gf2_matrix_times(&odd, crc1);
+    while times != 0 {
+        if times & 1 == 1 {
+            crc = op * crc + block;
         }
-        len2 >>= 1;
+        times >>= 1;
 
-        /* if no more bits set, then done */
-        if len2 == 0 {
-            break;
+        if times != 0 {
+            block = op * block + block;
+            op *= op;
         }
     }
 
-    /* return combined crc */
-    crc1 ^= crc2;
-    crc1
+    crc.to_register()
 }
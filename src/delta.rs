@@ -0,0 +1,544 @@
+//! Computes and applies rsync-style deltas between two versions of a file.
+//!
+//! The receiver, which holds the old version, computes a [Signature] of its blocks and sends it
+//! to the sender. The sender scans the new version with a rolling CRC-32C for blocks which also
+//! appear in the old version, and sends back a [Delta] made of copies from the old version and
+//! literal bytes. Since the weak checksum of each block is its CRC-32C, the CRC of the new
+//! version is obtained with [crc32c_combine](crate::crc32c_combine) as a by-product, and
+//! [apply_delta] uses it to detect a corrupted delta or the wrong old version.
+//!
+//! Blocks with the same length and CRC-32C are easy to craft, and such a false match produces
+//! a delta with the right CRC but the wrong output. Matches are thus only accepted once the
+//! strong hash of the block (e.g. a cryptographic hash, which the caller provides) matches too.
+//! [Signature::new_weak] and [diff_weak] skip the strong hash and match on the CRC alone, which
+//! is cheaper but only suitable when the new version is trusted: [apply_delta] cannot detect
+//! their false matches.
+//!
+//! ```rust
+//! use std::collections::hash_map::DefaultHasher;
+//! use std::hash::Hasher;
+//!
+//! use crc32c::delta::{apply_delta, diff, Signature};
+//!
+//! // Use a cryptographic hash (e.g. SHA-256) if the new version may be crafted.
+//! fn strong(block: &[u8]) -> Vec<u8> {
+//!     let mut hasher = DefaultHasher::new();
+//!     hasher.write(block);
+//!     hasher.finish().to_le_bytes().to_vec()
+//! }
+//!
+//! let old = b"The quick brown fox jumps over the lazy dog.".repeat(10);
+//! let new = [b"A prefix. ".as_slice(), &old[..200], b" an edit ", &old[200..]].concat();
+//!
+//! let signature = Signature::new(&old, 16, strong);
+//! let delta = diff(&signature, &new, strong);
+//!
+//! let mut out = Vec::new();
+//! apply_delta(&old, &delta, &mut out).unwrap();
+//!
+//! assert_eq!(out, new);
+//! assert_eq!(delta.crc, crc32c::crc32c(&new));
+//! ```
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+use crate::{crc32c, Crc32c, Crc32cRolling, Crc32cWriter};
+
+/// The checksums of a block of the old version.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Block {
+    /// CRC-32C of the block.
+    pub weak: u32,
+    /// Strong hash of the block.
+    pub strong: Vec<u8>,
+}
+
+/// The block checksums of the old version of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    block_size: usize,
+    len: u64,
+    blocks: Vec<Block>,
+}
+
+impl Signature {
+    /// Computes the signature of `old`, with the CRC-32C and the `strong` hash of each block.
+    ///
+    /// The strong hash is compared before accepting a block which matches the CRC, so a delta
+    /// is only as reliable as this hash.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0.
+    pub fn new<F>(old: &[u8], block_size: usize, mut strong: F) -> Self
+    where
+        F: FnMut(&[u8]) -> Vec<u8>,
+    {
+        assert!(block_size > 0, "block size must not be zero");
+
+        let blocks = old
+            .chunks(block_size)
+            .map(|block| Block {
+                weak: crc32c(block),
+                strong: strong(block),
+            })
+            .collect();
+
+        Self {
+            block_size,
+            len: old.len() as u64,
+            blocks,
+        }
+    }
+
+    /// Computes the signature of `old` with the CRC-32C of each block only, for [diff_weak].
+    ///
+    /// The blocks have an empty strong hash, so they never match in [diff].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0.
+    pub fn new_weak(old: &[u8], block_size: usize) -> Self {
+        Self::new(old, block_size, |_| Vec::new())
+    }
+
+    /// Create a signature from its parts, e.g. after receiving it from another host.
+    ///
+    /// Every block is `block_size` bytes long, except for the last one, which holds the
+    /// remaining bytes of the `len` bytes of the old version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0, or if the number of blocks does not match `len`.
+    pub fn from_parts(block_size: usize, len: u64, blocks: Vec<Block>) -> Self {
+        assert!(block_size > 0, "block size must not be zero");
+        assert_eq!(
+            blocks.len() as u64,
+            len.div_ceil(block_size as u64),
+            "wrong number of blocks"
+        );
+
+        Self {
+            block_size,
+            len,
+            blocks,
+        }
+    }
+
+    /// Get the size of the blocks.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Get the length of the old version.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the old version is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the checksums of the blocks.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Get the length of a block.
+    fn block_len(&self, block: usize) -> usize {
+        let start = (block * self.block_size) as u64;
+        (self.len - start).min(self.block_size as u64) as usize
+    }
+}
+
+/// An instruction to build the new version.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// Copy `len` bytes of the old version, starting at `offset`.
+    Copy {
+        /// Offset in the old version.
+        offset: u64,
+        /// Number of bytes to copy.
+        len: u64,
+    },
+    /// Insert these bytes.
+    Literal(Vec<u8>),
+}
+
+/// The instructions to build the new version of a file from the old one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Delta {
+    /// The instructions, in order.
+    pub ops: Vec<Op>,
+    /// Length of the new version.
+    pub len: u64,
+    /// CRC-32C of the new version.
+    pub crc: u32,
+}
+
+/// Accumulates the instructions of a delta, and the checksum of the new version.
+struct Builder {
+    ops: Vec<Op>,
+    digest: Crc32c,
+}
+
+impl Builder {
+    fn literal(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        self.digest.update(data);
+        self.ops.push(Op::Literal(data.to_vec()));
+    }
+
+    fn copy(&mut self, offset: u64, digest: Crc32c) {
+        self.digest += digest;
+
+        if let Some(Op::Copy { offset: last, len }) = self.ops.last_mut() {
+            if *last + *len == offset {
+                *len += digest.len();
+                return;
+            }
+        }
+
+        self.ops.push(Op::Copy {
+            offset,
+            len: digest.len(),
+        });
+    }
+}
+
+/// Computes the delta from the old version described by `signature` to `new`.
+///
+/// A block is copied when both its CRC-32C and its strong hash match. `strong` must be the
+/// function used to compute the signature.
+pub fn diff<F>(signature: &Signature, new: &[u8], mut strong: F) -> Delta
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    diff_with(signature, new, |block, data| {
+        strong(data) == signature.blocks[block].strong
+    })
+}
+
+/// Computes the delta from the old version described by `signature` to `new`, copying every
+/// block whose CRC-32C matches.
+///
+/// The strong hashes of the signature, if any, are ignored. A block of `new` with the same CRC
+/// as a different block of the old version is thus replaced by it, and since both have the
+/// same CRC, [apply_delta] succeeds with the wrong output. Only use this when `new` cannot be
+/// crafted, and accidental collisions (about one in 2^32 per matched block) are acceptable.
+pub fn diff_weak(signature: &Signature, new: &[u8]) -> Delta {
+    diff_with(signature, new, |_, _| true)
+}
+
+/// Computes the delta, accepting the blocks with a matching CRC-32C for which `matches` returns
+/// `true`.
+fn diff_with<F>(signature: &Signature, new: &[u8], mut matches: F) -> Delta
+where
+    F: FnMut(usize, &[u8]) -> bool,
+{
+    let block_size = signature.block_size;
+
+    // Only the full blocks can be found by the rolling CRC.
+    let mut lookup: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (i, block) in signature.blocks.iter().enumerate() {
+        if signature.block_len(i) == block_size {
+            lookup.entry(block.weak).or_default().push(i);
+        }
+    }
+
+    let mut builder = Builder {
+        ops: Vec::new(),
+        digest: Crc32c::default(),
+    };
+    let mut rolling = Crc32cRolling::new(block_size);
+    let mut literal = 0;
+
+    for (pos, &byte) in new.iter().enumerate() {
+        let crc = rolling.push(byte);
+
+        if !rolling.is_full() {
+            continue;
+        }
+
+        let start = pos + 1 - block_size;
+        let window = &new[start..=pos];
+        let found = lookup.get(&crc).and_then(|candidates| {
+            candidates
+                .iter()
+                .copied()
+                .find(|&block| matches(block, window))
+        });
+
+        if let Some(block) = found {
+            builder.literal(&new[literal..start]);
+            builder.copy(
+                (block * block_size) as u64,
+                Crc32c::from_parts(crc, block_size as u64),
+            );
+            literal = pos + 1;
+            rolling.reset();
+        }
+    }
+
+    // The last block of the old version may be partial, and can then only match at the end.
+    if let Some(last) = signature.blocks.len().checked_sub(1) {
+        let len = signature.block_len(last);
+        let block = &signature.blocks[last];
+
+        if len < block_size && new.len() - literal >= len {
+            let start = new.len() - len;
+            let tail = &new[start..];
+
+            if crc32c(tail) == block.weak && matches(last, tail) {
+                builder.literal(&new[literal..start]);
+                builder.copy(
+                    (last * block_size) as u64,
+                    Crc32c::from_parts(block.weak, len as u64),
+                );
+                literal = new.len();
+            }
+        }
+    }
+
+    builder.literal(&new[literal..]);
+
+    Delta {
+        ops: builder.ops,
+        len: builder.digest.len(),
+        crc: builder.digest.crc(),
+    }
+}
+
+/// Writes the new version built from `old` and the `delta` to `out`.
+///
+/// Returns an error of kind [io::ErrorKind::InvalidData] if a copy is out of the bounds of
+/// `old`, or if the CRC of the output does not match [Delta::crc], e.g. because the delta was
+/// corrupted or `old` is not the version the signature was computed from. In that case, the
+/// output was (at least partially) written, and should be discarded.
+///
+/// The CRC cannot detect a block of `old` which was matched by mistake, as it has the same CRC
+/// as the bytes it replaces: [diff] relies on the strong hash for that, while the output of
+/// [diff_weak] is only correct in the absence of such false matches.
+pub fn apply_delta<W: Write>(old: &[u8], delta: &Delta, out: W) -> io::Result<()> {
+    let mut writer = Crc32cWriter::new(out);
+
+    for op in &delta.ops {
+        match op {
+            Op::Copy { offset, len } => {
+                let range = usize::try_from(*offset)
+                    .ok()
+                    .zip(
+                        offset
+                            .checked_add(*len)
+                            .and_then(|end| usize::try_from(end).ok()),
+                    )
+                    .and_then(|(start, end)| old.get(start..end))
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "delta copy out of bounds")
+                    })?;
+
+                writer.write_all(range)?;
+            }
+            Op::Literal(data) => writer.write_all(data)?,
+        }
+    }
+
+    if writer.digest() != Crc32c::from_parts(delta.crc, delta.len) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "CRC-32C mismatch in the output of the delta",
+        ));
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    use rand::{Rng, RngCore};
+
+    use super::*;
+
+    fn random(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        rand::rng().fill_bytes(&mut data);
+        data
+    }
+
+    /// A strong hash which, unlike the CRC, is not linear.
+    fn strong(data: &[u8]) -> Vec<u8> {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(data);
+        hasher.finish().to_le_bytes().to_vec()
+    }
+
+    /// Checks both modes, and returns the delta with the strong hash.
+    fn round_trip(old: &[u8], new: &[u8], block_size: usize) -> Delta {
+        let signature = Signature::new(old, block_size, strong);
+        let delta = diff(&signature, new, strong);
+        let weak = diff_weak(&Signature::new_weak(old, block_size), new);
+
+        // Without crafted collisions, both modes find the same blocks.
+        assert_eq!(weak, delta);
+        assert_eq!(diff_weak(&signature, new), delta);
+
+        let mut out = Vec::new();
+        apply_delta(old, &delta, &mut out).unwrap();
+        assert_eq!(out, new);
+        assert_eq!(delta.crc, crc32c(new));
+        assert_eq!(delta.len, new.len() as u64);
+
+        delta
+    }
+
+    /// Returns a copy of `data` with the bytes in `range` changed, but with the same CRC.
+    fn collide(data: &[u8], range: std::ops::Range<usize>) -> Vec<u8> {
+        // A message followed by its unseeded CRC is a multiple of the polynomial, so adding it to
+        // a block keeps the CRC of the block.
+        let mut error = random(range.len() - 4);
+        error.extend_from_slice(&crate::crc32c_raw_update(0, &error).to_le_bytes());
+
+        let mut collided = data.to_vec();
+        for (byte, e) in collided[range.clone()].iter_mut().zip(&error) {
+            *byte ^= e;
+        }
+        assert_eq!(crc32c(&collided[range.clone()]), crc32c(&data[range]));
+
+        collided
+    }
+
+    fn literal_len(delta: &Delta) -> usize {
+        delta
+            .ops
+            .iter()
+            .map(|op| match op {
+                Op::Literal(data) => data.len(),
+                Op::Copy { .. } => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn identical() {
+        let old = random(10_000);
+
+        let delta = round_trip(&old, &old, 64);
+        assert_eq!(
+            delta.ops,
+            vec![Op::Copy {
+                offset: 0,
+                len: 10_000
+            }]
+        );
+    }
+
+    #[test]
+    fn edits() {
+        let mut rng = rand::rng();
+        let old = random(100_000);
+        let mut new = old.clone();
+
+        for _ in 0..10 {
+            let at = rng.random_range(0..new.len());
+            match rng.random_range(0..3) {
+                0 => {
+                    new.splice(at..at, random(rng.random_range(1..100)));
+                }
+                1 => {
+                    let end = new.len().min(at + rng.random_range(1..100));
+                    new.drain(at..end);
+                }
+                _ => new[at] ^= 0xFF,
+            }
+        }
+
+        let delta = round_trip(&old, &new, 256);
+
+        // Each edit makes at most two blocks and the inserted bytes literal.
+        assert!(literal_len(&delta) < 10 * (2 * 256 + 100));
+    }
+
+    #[test]
+    fn unrelated() {
+        let old = random(5000);
+        let new = random(7000);
+
+        let delta = round_trip(&old, &new, 100);
+        assert_eq!(delta.ops, vec![Op::Literal(new)]);
+
+        round_trip(&[], &old, 100);
+        round_trip(&old, &[], 100);
+        round_trip(&[], &[], 100);
+    }
+
+    #[test]
+    fn partial_last_block() {
+        let old = random(1050);
+        let new = [random(10).as_slice(), &old].concat();
+
+        let delta = round_trip(&old, &new, 100);
+        assert_eq!(literal_len(&delta), 10);
+    }
+
+    #[test]
+    fn rejects_false_match() {
+        let old = random(640);
+        let new = collide(&old, 128..192);
+
+        let signature = Signature::new(&old, 64, strong);
+        let delta = diff(&signature, &new, strong);
+
+        let mut out = Vec::new();
+        apply_delta(&old, &delta, &mut out).unwrap();
+        assert_eq!(out, new);
+        assert_eq!(literal_len(&delta), 64);
+    }
+
+    #[test]
+    fn weak_accepts_false_match() {
+        let old = random(640);
+        let new = collide(&old, 128..192);
+
+        let delta = diff_weak(&Signature::new_weak(&old, 64), &new);
+        assert_eq!(literal_len(&delta), 0);
+
+        // The CRC of the output matches, although it is the old version.
+        let mut out = Vec::new();
+        apply_delta(&old, &delta, &mut out).unwrap();
+        assert_eq!(out, old);
+    }
+
+    #[test]
+    fn detects_bad_delta() {
+        let old = random(1000);
+        let new = random(1000);
+
+        let mut delta = diff(&Signature::new(&old, 100, strong), &new, strong);
+        delta.crc ^= 1;
+        let err = apply_delta(&old, &delta, Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        delta.ops = vec![Op::Copy {
+            offset: 900,
+            len: 200,
+        }];
+        let err = apply_delta(&old, &delta, Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        delta.ops = vec![Op::Copy {
+            offset: u64::MAX,
+            len: 2,
+        }];
+        let err = apply_delta(&old, &delta, Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
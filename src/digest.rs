@@ -0,0 +1,317 @@
+//! Provides a CRC-32C value which knows the length of the data it covers.
+use std::iter::{FromIterator, Sum};
+use std::ops::{Add, AddAssign};
+use std::{error, fmt, io};
+
+use crate::vectored::Stitcher;
+use crate::{combine, crc32c_append};
+
+/// Version of the encoding produced by [Crc32c::to_bytes].
+const VERSION: u8 = 1;
+
+/// Error returned by [Crc32c::from_bytes] for an encoding it does not support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownVersion {
+    /// The version byte of the encoding.
+    pub version: u8,
+}
+
+impl fmt::Display for UnknownVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown CRC-32C state encoding version {}", self.version)
+    }
+}
+
+impl error::Error for UnknownVersion {}
+
+/// Error returned by [Crc32c::to_bytes] for a length which does not fit in the encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthOverflow {
+    /// The length of the data covered by the checksum.
+    pub len: u64,
+}
+
+impl fmt::Display for LengthOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "length {} does not fit in 56 bits", self.len)
+    }
+}
+
+impl error::Error for LengthOverflow {}
+
+/// Error returned when data does not match its expected checksum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    /// The expected checksum.
+    pub expected: u32,
+    /// The checksum of the data.
+    pub actual: u32,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CRC-32C mismatch (expected {:#010x}, got {:#010x})",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl error::Error for ChecksumMismatch {}
+
+impl From<ChecksumMismatch> for io::Error {
+    /// Converts to an error of kind [io::ErrorKind::InvalidData].
+    fn from(mismatch: ChecksumMismatch) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, mismatch)
+    }
+}
+
+/// A CRC-32C checksum, together with the number of bytes it covers.
+///
+/// Since [crc32c_combine](crate::crc32c_combine) needs the length of the second piece, keeping
+/// it next to the checksum allows partial results (e.g. from different threads or processes) to
+/// be concatenated in order without any extra bookkeeping.
+///
+/// # Example
+///
+/// ```rust
+/// use crc32c::Crc32c;
+///
+/// let hello = Crc32c::new(b"Hello ");
+/// let world = Crc32c::new(b"world!");
+///
+/// assert_eq!(hello + world, Crc32c::new(b"Hello world!"));
+/// assert_eq!((hello + world).crc(), 0x7B_98_E7_51);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Crc32c {
+    crc: u32,
+    len: u64,
+}
+
+impl Crc32c {
+    /// Computes the checksum of the data payload.
+    pub fn new(data: &[u8]) -> Self {
+        let mut digest = Self::default();
+        digest.update(data);
+        digest
+    }
+
+    /// Create the value from a checksum and the number of bytes it covers.
+    pub const fn from_parts(crc: u32, len: u64) -> Self {
+        Self { crc, len }
+    }
+
+    /// Get the checksum.
+    pub const fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    /// Get the number of bytes covered by the checksum.
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the checksum covers no bytes.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append the data payload to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        self.crc = crc32c_append(self.crc, data);
+        self.len += data.len() as u64;
+    }
+
+    /// Append `len` zero bytes to the checksum, without materializing them.
+    #[cfg_attr(not(all(target_os = "linux", feature = "sparse")), allow(dead_code))]
+    pub(crate) fn update_zeros(&mut self, len: u64) {
+        self.crc = combine::crc32c_append_zeros(self.crc, len);
+        self.len += len;
+    }
+
+    /// Encodes the value in a stable format, e.g. to checkpoint a long computation and resume it
+    /// after a restart with [Crc32cReader::resume](crate::Crc32cReader::resume) or
+    /// [Crc32cWriter::resume](crate::Crc32cWriter::resume).
+    ///
+    /// The encoding is the version (currently 1) as one byte, the length as 7 bytes and the
+    /// checksum as 4 bytes, both in little-endian order.
+    ///
+    /// Returns an error if the length does not fit in 56 bits (64 PiB), which no file can
+    /// reach, but which [Crc32c::from_parts] or [Crc32c::concat] can.
+    pub fn to_bytes(&self) -> Result<[u8; 12], LengthOverflow> {
+        if self.len >= 1 << 56 {
+            return Err(LengthOverflow { len: self.len });
+        }
+
+        let mut bytes = [0; 12];
+        bytes[..8].copy_from_slice(&self.len.to_le_bytes());
+        bytes[..8].rotate_right(1);
+        bytes[0] = VERSION;
+        bytes[8..].copy_from_slice(&self.crc.to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// Decodes a value encoded by [Crc32c::to_bytes].
+    pub fn from_bytes(bytes: [u8; 12]) -> Result<Self, UnknownVersion> {
+        if bytes[0] != VERSION {
+            return Err(UnknownVersion { version: bytes[0] });
+        }
+
+        let mut len = [0; 8];
+        len[..7].copy_from_slice(&bytes[1..8]);
+        let mut crc = [0; 4];
+        crc.copy_from_slice(&bytes[8..]);
+
+        Ok(Self {
+            crc: u32::from_le_bytes(crc),
+            len: u64::from_le_bytes(len),
+        })
+    }
+
+    /// Computes the checksum of this value's bytes, followed by `other`'s bytes.
+    pub fn concat(self, other: Self) -> Self {
+        Self {
+            crc: combine::crc32c_combine(self.crc, other.crc, other.len),
+            len: self.len + other.len,
+        }
+    }
+}
+
+impl Add for Crc32c {
+    type Output = Self;
+
+    /// Same as [Crc32c::concat]. Note that this is not commutative.
+    fn add(self, rhs: Self) -> Self::Output {
+        self.concat(rhs)
+    }
+}
+
+impl AddAssign for Crc32c {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = self.concat(rhs);
+    }
+}
+
+impl Sum for Crc32c {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Self::concat)
+    }
+}
+
+/// Appends each buffer, as with [crc32c_vectored](crate::crc32c_vectored).
+impl<'a> Extend<&'a [u8]> for Crc32c {
+    fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+        let mut stitcher = Stitcher::new(!self.crc);
+        for buf in iter {
+            stitcher.update(buf);
+            self.len += buf.len() as u64;
+        }
+        self.crc = !stitcher.finish();
+    }
+}
+
+impl<'a> FromIterator<&'a [u8]> for Crc32c {
+    fn from_iter<I: IntoIterator<Item = &'a [u8]>>(iter: I) -> Self {
+        let mut digest = Self::default();
+        digest.extend(iter);
+        digest
+    }
+}
+
+impl<'a> Sum<&'a Crc32c> for Crc32c {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+    use crate::crc32c;
+
+    #[test]
+    fn can_concat() {
+        let mut buf = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut buf);
+
+        let whole = Crc32c::new(&buf);
+        assert_eq!(whole.crc(), crc32c(&buf));
+        assert_eq!(whole.len(), 1000);
+
+        let (a, b) = buf.split_at(300);
+        assert_eq!(Crc32c::new(a) + Crc32c::new(b), whole);
+        assert_eq!(Crc32c::default() + whole, whole);
+        assert_eq!(whole + Crc32c::default(), whole);
+
+        let sum: Crc32c = buf.chunks(7).map(Crc32c::new).sum();
+        assert_eq!(sum, whole);
+    }
+
+    #[test]
+    fn can_encode() {
+        let digest = Crc32c::from_parts(0x12_34_56_78, 0x00_AB_CD_EF_01_23_45_67);
+        let bytes = digest.to_bytes().unwrap();
+
+        assert_eq!(
+            bytes,
+            [1, 0x67, 0x45, 0x23, 0x01, 0xEF, 0xCD, 0xAB, 0x78, 0x56, 0x34, 0x12]
+        );
+        assert_eq!(Crc32c::from_bytes(bytes), Ok(digest));
+        assert_eq!(
+            Crc32c::from_bytes(Crc32c::default().to_bytes().unwrap()),
+            Ok(Crc32c::default())
+        );
+
+        let mut bytes = bytes;
+        bytes[0] = 2;
+        assert_eq!(
+            Crc32c::from_bytes(bytes),
+            Err(UnknownVersion { version: 2 })
+        );
+
+        let digest = Crc32c::from_parts(0, 1 << 56);
+        assert_eq!(digest.to_bytes(), Err(LengthOverflow { len: 1 << 56 }));
+    }
+
+    #[test]
+    fn can_update() {
+        let mut buf = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut buf);
+
+        let mut digest = Crc32c::default();
+        for chunk in buf.chunks(100) {
+            digest.update(chunk);
+        }
+
+        assert_eq!(digest, Crc32c::new(&buf));
+    }
+
+    #[test]
+    fn can_collect() {
+        let mut buf = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut buf);
+
+        let digest: Crc32c = buf.chunks(7).collect();
+        assert_eq!(digest, Crc32c::new(&buf));
+
+        let mut digest = Crc32c::new(&buf[..100]);
+        digest.extend(buf[100..].chunks(300));
+        assert_eq!(digest, Crc32c::new(&buf));
+    }
+
+    #[test]
+    fn can_update_zeros() {
+        let mut buf = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut buf[..300]);
+
+        for len in [0, 1, 700] {
+            let mut digest = Crc32c::new(&buf[..300]);
+            digest.update_zeros(len);
+            assert_eq!(digest, Crc32c::new(&buf[..300 + len as usize]));
+        }
+    }
+}
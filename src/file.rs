@@ -0,0 +1,531 @@
+//! Checksums whole files at the speed of the hardware implementation.
+//!
+//! [checksum_path] reads the file with large, page-aligned buffers, or maps it into memory when
+//! the `mmap` feature is enabled and the file is large enough. Large files may also be split into
+//! ranges which are hashed by several threads, and whose CRCs are then merged with
+//! [crc32c_combine](crate::crc32c_combine). On Linux and with the `sparse` feature, the holes of
+//! sparse files can also be skipped rather than read.
+//!
+//! ```rust,no_run
+//! use crc32c::file::{checksum_path, Options};
+//!
+//! let progress = |hashed: u64| eprintln!("{hashed} bytes hashed");
+//! let options = Options::new().threads(4).progress(&progress);
+//!
+//! let digest = checksum_path("disk.img", &options).unwrap();
+//! println!("{:08x} ({} bytes)", digest.crc(), digest.len());
+//! ```
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::panic;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+use crate::Crc32c;
+
+/// The alignment of read buffers, which is the page size of most platforms.
+const ALIGN: usize = 4096;
+
+/// A progress callback, as passed to [Options::progress].
+type Progress<'a> = dyn Fn(u64) + Send + Sync + 'a;
+
+/// Options for [checksum_path].
+#[derive(Clone, Copy)]
+pub struct Options<'a> {
+    buffer_size: usize,
+    threads: usize,
+    chunk_size: u64,
+    #[cfg_attr(not(feature = "mmap"), allow(dead_code))]
+    mmap_threshold: u64,
+    #[cfg_attr(not(all(target_os = "linux", feature = "sparse")), allow(dead_code))]
+    sparse: bool,
+    progress: Option<&'a Progress<'a>>,
+}
+
+impl<'a> Options<'a> {
+    /// Creates the default options: 1 MiB buffers, a single thread, and memory maps for files
+    /// of 16 MiB or more.
+    pub fn new() -> Self {
+        Self {
+            buffer_size: 1 << 20,
+            threads: 1,
+            chunk_size: 16 << 20,
+            mmap_threshold: 16 << 20,
+            sparse: false,
+            progress: None,
+        }
+    }
+
+    /// Sets the size of the read buffers, which is also the granularity of progress reports.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn buffer_size(self, size: usize) -> Self {
+        assert!(size > 0, "buffer size must be positive");
+        Self {
+            buffer_size: size,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of threads hashing the file, or 0 to use
+    /// [available_parallelism](std::thread::available_parallelism).
+    pub fn threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
+    /// Sets the minimum number of bytes hashed by each thread, which limits the number of
+    /// threads used for smaller files.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunk_size(self, size: u64) -> Self {
+        assert!(size > 0, "chunk size must be positive");
+        Self {
+            chunk_size: size,
+            ..self
+        }
+    }
+
+    /// Sets the size from which files are mapped into memory rather than read.
+    ///
+    /// This has no effect unless the `mmap` feature is enabled.
+    pub fn mmap_threshold(self, len: u64) -> Self {
+        Self {
+            mmap_threshold: len,
+            ..self
+        }
+    }
+
+    /// Sets whether to skip the holes of sparse files, found with `SEEK_DATA` and `SEEK_HOLE`.
+    ///
+    /// Only the data extents are read, with a single thread, and each hole is accounted for in
+    /// `O(log(len))` time. The checksum is the same as if the holes were read as zeros. Files
+    /// on file systems which cannot find holes are read in full.
+    ///
+    /// This has no effect unless the `sparse` feature is enabled, on Linux.
+    pub fn sparse(self, sparse: bool) -> Self {
+        Self { sparse, ..self }
+    }
+
+    /// Sets a callback which is called with the number of bytes hashed so far, after each
+    /// buffer.
+    ///
+    /// When several threads hash the file, the callback is called from all of them, so calls
+    /// may overlap and the counts may arrive out of order.
+    pub fn progress(self, progress: &'a Progress<'a>) -> Self {
+        Self {
+            progress: Some(progress),
+            ..self
+        }
+    }
+
+    /// Returns the number of ranges, hashed by as many threads, for a file of `len` bytes.
+    fn ranges(&self, len: u64) -> usize {
+        let threads = match self.threads {
+            0 => thread::available_parallelism().map_or(1, usize::from),
+            threads => threads,
+        };
+
+        (len / self.chunk_size).clamp(1, threads as u64) as usize
+    }
+}
+
+impl Default for Options<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Counts the bytes hashed by all threads, and reports them to the progress callback.
+struct Counter<'a> {
+    hashed: AtomicU64,
+    progress: Option<&'a Progress<'a>>,
+}
+
+impl Counter<'_> {
+    fn add(&self, len: u64) {
+        let hashed = self.hashed.fetch_add(len, Ordering::Relaxed) + len;
+
+        if let Some(progress) = self.progress {
+            progress(hashed);
+        }
+    }
+}
+
+/// Computes the checksum of the file at `path`, with its length.
+///
+/// A file which is read (rather than mapped) with a single thread is hashed up to its end, even
+/// if it does not report a length (as for many special files). Otherwise, exactly the length
+/// reported when opening it is hashed, and the file must not shrink in the meantime.
+///
+/// # Safety of memory maps
+///
+/// With the `mmap` feature, another process truncating a mapped file makes the access to the
+/// missing pages fail with `SIGBUS` on most platforms. Set a `mmap_threshold` of [u64::MAX] to
+/// never map files which may be modified concurrently.
+pub fn checksum_path<P: AsRef<Path>>(path: P, options: &Options<'_>) -> io::Result<Crc32c> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    let counter = Counter {
+        hashed: AtomicU64::new(0),
+        progress: options.progress,
+    };
+
+    #[cfg(all(target_os = "linux", feature = "sparse"))]
+    {
+        if options.sparse {
+            return sparse::checksum_sparse(file, len, options, &counter);
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    {
+        if len > 0 && len >= options.mmap_threshold {
+            return checksum_mmap(&file, options, &counter);
+        }
+    }
+
+    let ranges = if cfg!(any(unix, windows)) {
+        options.ranges(len)
+    } else {
+        1
+    };
+    if ranges == 1 {
+        return checksum_reader(file, options.buffer_size, &counter);
+    }
+
+    // The threads share the handle, so they all read the same file even if the path is replaced
+    // in the meantime.
+    in_parallel(vec![&file; ranges], |i, file| {
+        let (start, end) = range(len, ranges, i);
+        let reader = RangeReader {
+            file,
+            offset: start,
+            end,
+        };
+
+        let digest = checksum_reader(reader, options.buffer_size, &counter)?;
+        if digest.len() != end - start {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(digest)
+    })
+}
+
+/// Returns the bounds of the `i`-th of `ranges` ranges of `len` bytes.
+fn range(len: u64, ranges: usize, i: usize) -> (u64, u64) {
+    let bound = |i: usize| (len as u128 * i as u128 / ranges as u128) as u64;
+    (bound(i), bound(i + 1))
+}
+
+/// Hashes each item on its own thread, and combines the results in order.
+fn in_parallel<T, F>(items: Vec<T>, f: F) -> io::Result<Crc32c>
+where
+    T: Send,
+    F: Fn(usize, T) -> io::Result<Crc32c> + Sync,
+{
+    let f = &f;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| scope.spawn(move || f(i, item)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .sum()
+    })
+}
+
+/// Reads a range of a file with positional reads, which do not move the shared file offset.
+struct RangeReader<'a> {
+    file: &'a File,
+    offset: u64,
+    end: u64,
+}
+
+impl Read for RangeReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = (self.end - self.offset).min(buf.len() as u64) as usize;
+        let n = read_at(self.file, &mut buf[..len], self.offset)?;
+
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+/// Files are hashed by a single thread on other platforms.
+#[cfg(not(any(unix, windows)))]
+fn read_at(_: &File, _: &mut [u8], _: u64) -> io::Result<usize> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Hashes a reader to its end, with an aligned buffer of `size` bytes.
+fn checksum_reader<R: Read>(mut reader: R, size: usize, counter: &Counter) -> io::Result<Crc32c> {
+    let mut storage = vec![0; size + ALIGN - 1];
+
+    // The alignment only makes the reads faster, so give up on it if it cannot be computed.
+    let offset = match storage.as_ptr().align_offset(ALIGN) {
+        offset if offset < ALIGN => offset,
+        _ => 0,
+    };
+    let buf = &mut storage[offset..offset + size];
+
+    let mut digest = Crc32c::default();
+
+    loop {
+        let n = match reader.read(buf) {
+            Ok(0) => return Ok(digest),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        digest.update(&buf[..n]);
+        counter.add(n as u64);
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn checksum_mmap(file: &File, options: &Options<'_>, counter: &Counter) -> io::Result<Crc32c> {
+    // SAFETY: the map is only read, and the caller is warned about concurrent truncation.
+    let map = unsafe { memmap2::Mmap::map(file)? };
+
+    let ranges = options.ranges(map.len() as u64);
+    let parts = (0..ranges)
+        .map(|i| {
+            let (start, end) = range(map.len() as u64, ranges, i);
+            &map[start as usize..end as usize]
+        })
+        .collect();
+
+    in_parallel(parts, |_, part: &[u8]| {
+        let mut digest = Crc32c::default();
+
+        for buf in part.chunks(options.buffer_size) {
+            digest.update(buf);
+            counter.add(buf.len() as u64);
+        }
+
+        Ok(digest)
+    })
+}
+
+#[cfg(all(target_os = "linux", feature = "sparse"))]
+mod sparse {
+    use std::convert::TryFrom;
+    use std::io::{Seek, SeekFrom};
+    use std::os::unix::io::AsRawFd;
+
+    use super::*;
+
+    /// Returns whether `lseek` failed because the file system does not support `SEEK_DATA` and
+    /// `SEEK_HOLE`.
+    fn unsupported(e: &io::Error) -> bool {
+        matches!(
+            e.raw_os_error(),
+            Some(libc::EINVAL) | Some(libc::EOPNOTSUPP)
+        )
+    }
+
+    /// Seeks to the next data or hole at or after `offset`, or returns `None` if the rest of the
+    /// file is a hole.
+    fn seek(file: &File, offset: u64, whence: libc::c_int) -> io::Result<Option<u64>> {
+        let offset = libc::off_t::try_from(offset)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        // SAFETY: lseek has no memory safety requirements.
+        match unsafe { libc::lseek(file.as_raw_fd(), offset, whence) } {
+            -1 => match io::Error::last_os_error() {
+                e if e.raw_os_error() == Some(libc::ENXIO) => Ok(None),
+                e => Err(e),
+            },
+            pos => Ok(Some(pos as u64)),
+        }
+    }
+
+    /// Hashes the first `len` bytes of the file, reading only its data extents.
+    pub(super) fn checksum_sparse(
+        mut file: File,
+        len: u64,
+        options: &Options<'_>,
+        counter: &Counter,
+    ) -> io::Result<Crc32c> {
+        let mut digest = Crc32c::default();
+
+        while digest.len() < len {
+            let pos = digest.len();
+
+            let data = match seek(&file, pos, libc::SEEK_DATA) {
+                Ok(data) => data.map_or(len, |data| data.min(len)),
+                // The file system cannot find holes, so the rest of the file is read.
+                Err(e) if unsupported(&e) => pos,
+                Err(e) => return Err(e),
+            };
+            digest.update_zeros(data - pos);
+            counter.add(data - pos);
+
+            if data == len {
+                break;
+            }
+
+            // There is always an implicit hole at the end of the file.
+            let hole = match seek(&file, data, libc::SEEK_HOLE) {
+                Ok(hole) => hole.map_or(len, |hole| hole.min(len)),
+                Err(e) if unsupported(&e) => len,
+                Err(e) => return Err(e),
+            };
+            file.seek(SeekFrom::Start(data))?;
+
+            let extent = (&mut file).take(hole - data);
+            digest += checksum_reader(extent, options.buffer_size, counter)?;
+
+            if digest.len() != hole {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use rand::RngCore;
+
+    use super::*;
+
+    /// A temporary file, removed on drop.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, data: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!("crc32c-{}-{name}", std::process::id()));
+            fs::write(&path, data).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn random_data(len: usize) -> Vec<u8> {
+        let mut data = vec![0; len];
+        rand::rng().fill_bytes(&mut data);
+        data
+    }
+
+    #[test]
+    fn can_checksum() {
+        let data = random_data(100_000);
+        let file = TempFile::new("can_checksum", &data);
+
+        for threads in [1, 3, 0] {
+            for mmap_threshold in [0, u64::MAX] {
+                let options = Options::new()
+                    .buffer_size(4000)
+                    .chunk_size(1000)
+                    .threads(threads)
+                    .mmap_threshold(mmap_threshold);
+
+                let digest = checksum_path(&file.0, &options).unwrap();
+                assert_eq!(digest, Crc32c::new(&data));
+            }
+        }
+
+        let empty = TempFile::new("can_checksum_empty", &[]);
+        let digest = checksum_path(&empty.0, &Options::new().mmap_threshold(0)).unwrap();
+        assert_eq!(digest, Crc32c::default());
+    }
+
+    #[test]
+    fn reports_progress() {
+        let data = random_data(50_000);
+        let file = TempFile::new("reports_progress", &data);
+
+        for threads in [1, 4] {
+            let reports = Mutex::new(Vec::new());
+            let progress = |hashed| reports.lock().unwrap().push(hashed);
+            let options = Options::new()
+                .buffer_size(1024)
+                .chunk_size(1024)
+                .threads(threads)
+                .progress(&progress);
+
+            checksum_path(&file.0, &options).unwrap();
+
+            let mut reports = reports.into_inner().unwrap();
+            reports.sort_unstable();
+            assert!(reports.len() >= data.len().div_ceil(1024));
+            assert_eq!(reports.last(), Some(&(data.len() as u64)));
+        }
+    }
+
+    #[cfg(all(target_os = "linux", feature = "sparse"))]
+    #[test]
+    fn skips_holes() {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let mut data = vec![0; 3 << 20];
+        rand::rng().fill_bytes(&mut data[1 << 20..(1 << 20) + 5000]);
+        rand::rng().fill_bytes(&mut data[(2 << 20) + 1..(2 << 20) + 100]);
+
+        for (name, len) in [
+            ("skips_holes", data.len()),
+            ("skips_holes_data", (2 << 20) + 100),
+        ] {
+            let temp = TempFile::new(name, &[]);
+            let mut file = fs::OpenOptions::new().write(true).open(&temp.0).unwrap();
+
+            file.set_len(len as u64).unwrap();
+            for range in [(1 << 20)..(1 << 20) + 5000, (2 << 20) + 1..(2 << 20) + 100] {
+                file.seek(SeekFrom::Start(range.start as u64)).unwrap();
+                file.write_all(&data[range]).unwrap();
+            }
+            drop(file);
+
+            let reports = Mutex::new(Vec::new());
+            let progress = |hashed| reports.lock().unwrap().push(hashed);
+            let options = Options::new().sparse(true).progress(&progress);
+
+            let digest = checksum_path(&temp.0, &options).unwrap();
+            assert_eq!(digest, Crc32c::new(&data[..len]));
+            assert_eq!(reports.into_inner().unwrap().last(), Some(&(len as u64)));
+        }
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = std::env::temp_dir().join("crc32c-missing-file");
+        let err = checksum_path(path, &Options::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,279 @@
+//! Provides a software CRC-32 engine for other polynomials and conventions.
+//!
+//! The engine shares the table-driven software implementation and the combine operators with
+//! CRC-32C. Hardware acceleration is only used for the CRC-32C parameters.
+//!
+//! # Example
+//!
+//! ```rust
+//! use crc32c::generic::{Crc32, CRC_32_ISO_HDLC};
+//!
+//! let crc32 = Crc32::new(CRC_32_ISO_HDLC);
+//!
+//! assert_eq!(crc32.checksum(b"123456789"), 0xCB_F4_39_26);
+//! ```
+
+use crate::poly::{self, Poly32};
+use crate::sw::{self, CrcTable};
+
+/// Parameters of a CRC-32 algorithm, following the catalogue of parametrised CRC algorithms.
+///
+/// See <https://reveng.sourceforge.io/crc-catalogue/17plus.htm>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Params {
+    /// The generator polynomial, in the normal bit order, without the `x^32` term.
+    pub poly: u32,
+    /// Initial value of the register.
+    pub init: u32,
+    /// Whether the bits of each byte are processed least significant bit first, and the final
+    /// register reflected.
+    pub reflected: bool,
+    /// Value XOR-ed into the final register.
+    pub xorout: u32,
+    /// CRC of the ASCII string `"123456789"`.
+    pub check: u32,
+}
+
+/// CRC-32C (Castagnoli), as used by iSCSI, SCTP and ext4. Also known as CRC-32/ISCSI.
+pub const CRC_32C: Params = Params {
+    poly: 0x1E_DC_6F_41,
+    init: 0xFF_FF_FF_FF,
+    reflected: true,
+    xorout: 0xFF_FF_FF_FF,
+    check: 0xE3_06_92_83,
+};
+
+/// CRC-32 (IEEE 802.3), as used by Ethernet, zlib, gzip and PNG. Also known as CRC-32/ISO-HDLC.
+pub const CRC_32_ISO_HDLC: Params = Params {
+    poly: 0x04_C1_1D_B7,
+    init: 0xFF_FF_FF_FF,
+    reflected: true,
+    xorout: 0xFF_FF_FF_FF,
+    check: 0xCB_F4_39_26,
+};
+
+/// The IEEE polynomial without final inversion, as used by JAM.
+pub const CRC_32_JAMCRC: Params = Params {
+    poly: 0x04_C1_1D_B7,
+    init: 0xFF_FF_FF_FF,
+    reflected: true,
+    xorout: 0x00_00_00_00,
+    check: 0x34_0B_C6_D9,
+};
+
+/// The IEEE polynomial, not reflected, as used by bzip2.
+pub const CRC_32_BZIP2: Params = Params {
+    poly: 0x04_C1_1D_B7,
+    init: 0xFF_FF_FF_FF,
+    reflected: false,
+    xorout: 0xFF_FF_FF_FF,
+    check: 0xFC_89_19_18,
+};
+
+/// The IEEE polynomial, not reflected, as used by MPEG-2 transport streams.
+pub const CRC_32_MPEG_2: Params = Params {
+    poly: 0x04_C1_1D_B7,
+    init: 0xFF_FF_FF_FF,
+    reflected: false,
+    xorout: 0x00_00_00_00,
+    check: 0x03_76_E6_E7,
+};
+
+/// The IEEE polynomial, as used by the POSIX `cksum` utility (without its length suffix).
+pub const CRC_32_CKSUM: Params = Params {
+    poly: 0x04_C1_1D_B7,
+    init: 0x00_00_00_00,
+    reflected: false,
+    xorout: 0xFF_FF_FF_FF,
+    check: 0x76_5E_76_80,
+};
+
+/// CRC-32K, with Koopman's polynomial, as used by MEF. Also known as CRC-32/MEF.
+pub const CRC_32K: Params = Params {
+    poly: 0x74_1B_8C_D7,
+    init: 0xFF_FF_FF_FF,
+    reflected: true,
+    xorout: 0x00_00_00_00,
+    check: 0xD2_C2_2F_51,
+};
+
+/// CRC-32Q, as used by aeronautical data (AIXM). Also known as CRC-32/AIXM.
+pub const CRC_32Q: Params = Params {
+    poly: 0x81_41_41_AB,
+    init: 0x00_00_00_00,
+    reflected: false,
+    xorout: 0x00_00_00_00,
+    check: 0x30_10_BF_7F,
+};
+
+/// The polynomial used by AUTOSAR. Also known as CRC-32/AUTOSAR.
+pub const CRC_32_AUTOSAR: Params = Params {
+    poly: 0xF4_AC_FB_13,
+    init: 0xFF_FF_FF_FF,
+    reflected: true,
+    xorout: 0xFF_FF_FF_FF,
+    check: 0x16_97_D0_6A,
+};
+
+/// A CRC-32 algorithm, with its lookup table.
+///
+/// Building one generates an 8-KiB table, so create it once and reuse it.
+pub struct Crc32 {
+    params: Params,
+    /// The polynomial in the reflected bit order.
+    polynomial: u32,
+    /// Whether the parameters define CRC-32C, which has a hardware implementation.
+    hardware: bool,
+    table: Box<CrcTable>,
+}
+
+impl Crc32 {
+    /// Create the algorithm for a set of parameters.
+    pub fn new(params: Params) -> Self {
+        let polynomial = params.poly.reverse_bits();
+
+        // The check value only describes the algorithm, so it is not compared.
+        let hardware = params.poly == CRC_32C.poly
+            && params.init == CRC_32C.init
+            && params.reflected == CRC_32C.reflected
+            && params.xorout == CRC_32C.xorout;
+
+        Self {
+            params,
+            polynomial,
+            hardware,
+            table: Box::new(CrcTable::new(polynomial)),
+        }
+    }
+
+    /// Get the parameters of the algorithm.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Computes the CRC for the data payload.
+    ///
+    /// Equivalent to calling `append(crc, data)`, with the CRC of an empty payload.
+    pub fn checksum(&self, data: &[u8]) -> u32 {
+        self.append(self.params.init ^ self.params.xorout, data)
+    }
+
+    /// Computes the CRC for the data payload, starting with a previous CRC value.
+    pub fn append(&self, crc: u32, data: &[u8]) -> u32 {
+        if self.hardware {
+            return crate::crc32c_append(crc, data);
+        }
+
+        let register = self.to_reflected(crc ^ self.params.xorout);
+
+        let register = if self.params.reflected {
+            sw::update::<false>(&self.table, register, data)
+        } else {
+            sw::update::<true>(&self.table, register, data)
+        };
+
+        self.to_reflected(register) ^ self.params.xorout
+    }
+
+    /// Computes the "combined" value of two CRC values. Specifically, given two byte streams A
+    /// and B and their CRC check values, this function calculates the CRC of AB using only the
+    /// CRCs of A and B, and the length of B.
+    pub fn combine(&self, crc1: u32, crc2: u32, len2: usize) -> u32 {
+        if len2 == 0 {
+            return crc1;
+        }
+
+        // Unlike CRC-32C, the seed and final XOR do not necessarily cancel out.
+        let crc1 = self.to_reflected(crc1 ^ self.params.xorout ^ self.params.init);
+
+        let x = Poly32::X.to_register();
+        let op = poly::pow_mod(x, len2 as u64, self.polynomial);
+        let op = poly::pow_mod(op, 8, self.polynomial);
+        let shifted = poly::multiply_mod(op, crc1, self.polynomial);
+
+        self.to_reflected(shifted) ^ crc2
+    }
+
+    /// Converts between the register of the algorithm and the reflected one used internally.
+    fn to_reflected(&self, register: u32) -> u32 {
+        if self.params.reflected {
+            register
+        } else {
+            register.reverse_bits()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    const PRESETS: [Params; 9] = [
+        CRC_32C,
+        CRC_32_ISO_HDLC,
+        CRC_32_JAMCRC,
+        CRC_32_BZIP2,
+        CRC_32_MPEG_2,
+        CRC_32_CKSUM,
+        CRC_32K,
+        CRC_32Q,
+        CRC_32_AUTOSAR,
+    ];
+
+    #[test]
+    fn check() {
+        for params in PRESETS {
+            let crc = Crc32::new(params);
+
+            assert_eq!(crc.checksum(b"123456789"), params.check, "{params:?}");
+        }
+    }
+
+    #[test]
+    fn sw_matches_hw() {
+        let mut sw = Crc32::new(CRC_32C);
+        sw.hardware = false;
+
+        let mut buf = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut buf);
+
+        assert_eq!(sw.checksum(&buf), crate::crc32c(&buf));
+    }
+
+    #[test]
+    fn hw_ignores_check() {
+        let params = Params {
+            check: 0,
+            ..CRC_32C
+        };
+
+        assert!(Crc32::new(CRC_32C).hardware);
+        assert!(Crc32::new(params).hardware);
+        assert!(!Crc32::new(CRC_32_AUTOSAR).hardware);
+    }
+
+    #[test]
+    fn can_append_and_combine() {
+        let mut a = vec![0u8; 37];
+        let mut b = vec![0u8; 100];
+        rand::rng().fill_bytes(&mut a);
+        rand::rng().fill_bytes(&mut b);
+        let ab = [a.as_slice(), b.as_slice()].concat();
+
+        for params in PRESETS {
+            let crc = Crc32::new(params);
+
+            let (crc_a, crc_b, crc_ab) = (crc.checksum(&a), crc.checksum(&b), crc.checksum(&ab));
+
+            assert_eq!(crc.append(crc_a, &b), crc_ab, "{params:?}");
+            assert_eq!(crc.combine(crc_a, crc_b, b.len()), crc_ab, "{params:?}");
+            assert_eq!(
+                crc.combine(crc_a, crc.checksum(&[]), 0),
+                crc_a,
+                "{params:?}"
+            );
+        }
+    }
+}
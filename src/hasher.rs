@@ -1,7 +1,7 @@
 //! Provide a CRC-32C implementor of [Hasher].
 use std::hash::Hasher;
 
-use crate::crc32c_append;
+use crate::{crc32c_append, Crc32c};
 
 /// Implementor of [Hasher] for CRC-32C.
 ///
@@ -10,6 +10,7 @@ use crate::crc32c_append;
 #[derive(Default)]
 pub struct Crc32cHasher {
     checksum: u32,
+    len: u64,
 }
 
 impl Crc32cHasher {
@@ -17,7 +18,26 @@ impl Crc32cHasher {
     ///
     /// Use the [Default::default()] constructor for a clean start.
     pub fn new(initial: u32) -> Self {
-        Self { checksum: initial }
+        Self {
+            checksum: initial,
+            len: 0,
+        }
+    }
+
+    /// Create the [Hasher] from the state of a previous one, saved with [Crc32c::to_bytes].
+    pub fn resume(state: Crc32c) -> Self {
+        Self {
+            checksum: state.crc(),
+            len: state.len(),
+        }
+    }
+
+    /// Get the checksum of all bytes written, with their count.
+    ///
+    /// The count includes the bytes written to the [Hasher] and those covered by the state it
+    /// was resumed from, but not those covered by the initial checksum.
+    pub fn digest(&self) -> Crc32c {
+        Crc32c::from_parts(self.checksum, self.len)
     }
 }
 
@@ -28,6 +48,7 @@ impl Hasher for Crc32cHasher {
 
     fn write(&mut self, bytes: &[u8]) {
         self.checksum = crc32c_append(self.checksum, bytes);
+        self.len += bytes.len() as u64;
     }
 }
 
@@ -44,5 +65,20 @@ mod tests {
         let mut hasher = Crc32cHasher::default();
         hasher.write(TEST_STRING);
         assert_eq!(hasher.finish(), CHECKSUM as u64);
+        assert_eq!(hasher.digest(), Crc32c::new(TEST_STRING));
+    }
+
+    #[test]
+    fn can_resume() {
+        for offset in [0, 1, 17, TEST_STRING.len()] {
+            let mut hasher = Crc32cHasher::default();
+            hasher.write(&TEST_STRING[..offset]);
+            let state = Crc32c::from_bytes(hasher.digest().to_bytes().unwrap()).unwrap();
+
+            let mut hasher = Crc32cHasher::resume(state);
+            hasher.write(&TEST_STRING[offset..]);
+            assert_eq!(hasher.finish(), CHECKSUM as u64);
+            assert_eq!(hasher.digest(), Crc32c::new(TEST_STRING));
+        }
     }
 }
@@ -2,8 +2,8 @@ use crate::hw_tables;
 use crate::util::{self, U64Le};
 use std::arch::aarch64 as simd;
 
-pub unsafe fn crc32c(crci: u32, buffer: &[u8]) -> u32 {
-    let mut crc0 = !crci;
+pub unsafe fn crc32c_raw(crc: u32, buffer: &[u8]) -> u32 {
+    let mut crc0 = crc;
     let (begin, middle, end) = util::split(buffer);
 
     // We're effectively cheating by using the software implementation
@@ -39,7 +39,7 @@ pub unsafe fn crc32c(crci: u32, buffer: &[u8]) -> u32 {
     // Now the last part, less than SHORT * 3 but still a multiple of 8-bytes.
     crc0 = crc_u64(crc0, middle_last_last);
 
-    !crc_u8(crc0, end)
+    crc_u8(crc0, end)
 }
 
 #[inline]
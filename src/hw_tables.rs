@@ -1,7 +1,16 @@
+use crate::poly::POLYNOMIAL;
+use crate::tables;
+
+#[derive(Clone)]
 pub struct CrcTable([[u32; 256]; 4]);
 
 #[allow(dead_code)]
 impl CrcTable {
+    /// Builds the table for the operator which shifts a CRC past `len` zero bytes.
+    pub fn new(len: usize) -> Self {
+        Self(tables::hw_table(POLYNOMIAL, len))
+    }
+
     pub fn at(&self, i: u8, j: u8) -> u32 {
         let i = i as usize;
         let j = j as usize;
@@ -31,8 +40,12 @@ impl CrcTable {
     }
 }
 
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 pub const LONG: usize = 8192;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 pub const SHORT: usize = 256;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 pub const LONG_TABLE: CrcTable = CrcTable(include!(concat!(env!("OUT_DIR"), "/", "hw.long.table")));
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 pub const SHORT_TABLE: CrcTable =
     CrcTable(include!(concat!(env!("OUT_DIR"), "/", "hw.short.table")));
@@ -4,9 +4,9 @@ use crate::hw_tables;
 use crate::util::{self, U64Le};
 use std::arch::x86_64 as simd;
 
-/// Computes CRC-32C using the SSE 4.2 hardware instruction.
-pub unsafe fn crc32c(crci: u32, buffer: &[u8]) -> u32 {
-    let mut crc0 = u64::from(!crci);
+/// Computes CRC-32C using the SSE 4.2 hardware instruction, on the raw CRC register.
+pub unsafe fn crc32c_raw(crc: u32, buffer: &[u8]) -> u32 {
+    let mut crc0 = u64::from(crc);
 
     let (begin, middle, end) = util::split(buffer);
 
@@ -39,7 +39,7 @@ pub unsafe fn crc32c(crci: u32, buffer: &[u8]) -> u32 {
     // Final unaligned remainder.
     crc0 = crc_u8(crc0, end);
 
-    !(crc0 as u32)
+    crc0 as u32
 }
 
 #[inline]
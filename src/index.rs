@@ -0,0 +1,323 @@
+//! Answers CRC-32C queries over arbitrary byte ranges from per-block checksums.
+use std::io;
+use std::ops::Range;
+
+use crate::{combine, crc32c_append, Crc32c};
+
+/// An index over the checksums of the fixed-size blocks of an object.
+///
+/// The checksums are kept in a segment tree, whose nodes hold the checksum of consecutive blocks
+/// merged with [crc32c_combine](crate::crc32c_combine). The CRC of any byte range is then computed
+/// from `O(log n)` nodes, plus the bytes of the partial blocks at the edges of the range, which
+/// are read through a callback. Replacing the checksum of a block also takes `O(log n)`.
+///
+/// # Example
+///
+/// ```rust
+/// use crc32c::Crc32cIndex;
+///
+/// let data = b"Hello world! This is an index.";
+/// let index = Crc32cIndex::new(data, 4);
+///
+/// let crc = index
+///     .query(0..12, |offset, buf| {
+///         let offset = offset as usize;
+///         buf.copy_from_slice(&data[offset..offset + buf.len()]);
+///         Ok(())
+///     })
+///     .unwrap();
+///
+/// assert_eq!(crc, 0x7B_98_E7_51);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Crc32cIndex {
+    block_size: usize,
+    len: u64,
+    blocks: usize,
+    /// The nodes of the tree: the blocks are the leaves, starting at index `blocks`, and node `i`
+    /// merges nodes `2 * i` and `2 * i + 1`.
+    tree: Vec<Crc32c>,
+}
+
+impl Crc32cIndex {
+    /// Build the index by computing the checksum of each `block_size` bytes of `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0.
+    pub fn new(data: &[u8], block_size: usize) -> Self {
+        assert!(block_size > 0, "block size must not be zero");
+
+        Self::from_digests(
+            block_size,
+            data.len() as u64,
+            data.chunks(block_size).map(Crc32c::new),
+        )
+    }
+
+    /// Build the index from the stored checksums of each block of an object of `len` bytes.
+    ///
+    /// Every block is `block_size` bytes long, except for the last one, which holds the
+    /// remaining bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0, or if the number of checksums does not match `len`.
+    pub fn from_block_crcs(block_size: usize, len: u64, crcs: &[u32]) -> Self {
+        assert!(block_size > 0, "block size must not be zero");
+        assert_eq!(
+            crcs.len() as u64,
+            len.div_ceil(block_size as u64),
+            "wrong number of block checksums"
+        );
+
+        let digests = crcs.iter().enumerate().map(|(block, &crc)| {
+            let start = (block * block_size) as u64;
+            let block_len = (block_size as u64).min(len - start);
+            Crc32c::from_parts(crc, block_len)
+        });
+
+        Self::from_digests(block_size, len, digests)
+    }
+
+    fn from_digests(block_size: usize, len: u64, digests: impl Iterator<Item = Crc32c>) -> Self {
+        let blocks = len.div_ceil(block_size as u64) as usize;
+        let mut tree = vec![Crc32c::default(); blocks];
+        tree.extend(digests);
+
+        for i in (1..blocks).rev() {
+            tree[i] = tree[2 * i] + tree[2 * i + 1];
+        }
+
+        Self {
+            block_size,
+            len,
+            blocks,
+            tree,
+        }
+    }
+
+    /// Get the size of the blocks.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Get the number of bytes covered by the index.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the index covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of blocks.
+    pub fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    /// Get the checksum of a block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is out of bounds.
+    pub fn block_crc32c(&self, block: usize) -> u32 {
+        assert!(block < self.blocks, "block out of bounds");
+
+        self.tree[self.blocks + block].crc()
+    }
+
+    /// Get the checksum of the whole object.
+    pub fn crc32c(&self) -> u32 {
+        self.merge(0..self.blocks).crc()
+    }
+
+    /// Replace the checksum of a block, after its data changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is out of bounds.
+    pub fn update(&mut self, block: usize, crc: u32) {
+        assert!(block < self.blocks, "block out of bounds");
+
+        let mut i = self.blocks + block;
+        self.tree[i] = Crc32c::from_parts(crc, self.tree[i].len());
+
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = self.tree[2 * i] + self.tree[2 * i + 1];
+        }
+    }
+
+    /// Computes the CRC of the bytes in `range`.
+    ///
+    /// The blocks fully inside the range are taken from the index. The bytes of the blocks only
+    /// partially covered are read with `read_at(offset, buf)`, which must fill `buf` with the
+    /// bytes starting at `offset` (e.g. [FileExt::read_exact_at](std::os::unix::fs::FileExt)).
+    /// At most two calls are made, for less than a block each, except when no block is fully
+    /// inside the range: a single call then reads the whole range, which spans less than two
+    /// blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range extends past the end of the index.
+    pub fn query<F>(&self, range: Range<u64>, mut read_at: F) -> io::Result<u32>
+    where
+        F: FnMut(u64, &mut [u8]) -> io::Result<()>,
+    {
+        assert!(range.end <= self.len, "range out of bounds");
+
+        if range.start >= range.end {
+            return Ok(0);
+        }
+
+        let block_size = self.block_size as u64;
+        let mut read = |range: Range<u64>, crc: u32| -> io::Result<u32> {
+            if range.is_empty() {
+                return Ok(crc);
+            }
+
+            let mut buf = vec![0; (range.end - range.start) as usize];
+            read_at(range.start, &mut buf)?;
+            Ok(crc32c_append(crc, &buf))
+        };
+
+        let first = range.start.div_ceil(block_size);
+        let last = if range.end == self.len {
+            // The last block may be partial, but it is then fully inside the range.
+            self.blocks as u64
+        } else {
+            range.end / block_size
+        };
+
+        if first >= last {
+            // No block is fully inside the range.
+            return read(range, 0);
+        }
+
+        let crc = read(range.start..first * block_size, 0)?;
+        let inner = self.merge(first as usize..last as usize);
+        let crc = combine::crc32c_combine(crc, inner.crc(), inner.len());
+
+        read(self.len.min(last * block_size)..range.end, crc)
+    }
+
+    /// Merges the checksums of `blocks`.
+    fn merge(&self, blocks: Range<usize>) -> Crc32c {
+        let (mut left, mut right) = (Crc32c::default(), Crc32c::default());
+        let (mut l, mut r) = (blocks.start + self.blocks, blocks.end + self.blocks);
+
+        while l < r {
+            if l % 2 == 1 {
+                left += self.tree[l];
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                right = self.tree[r] + right;
+            }
+            l /= 2;
+            r /= 2;
+        }
+
+        left + right
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, RngCore};
+
+    use super::*;
+    use crate::crc32c;
+
+    fn reader(data: &[u8]) -> impl FnMut(u64, &mut [u8]) -> io::Result<()> + '_ {
+        move |offset, buf| {
+            let offset = offset as usize;
+            buf.copy_from_slice(&data[offset..offset + buf.len()]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn can_query() {
+        let mut rng = rand::rng();
+
+        for len in [0, 1, 99, 100, 1000, 1234] {
+            let mut data = vec![0u8; len];
+            rng.fill_bytes(&mut data);
+
+            let index = Crc32cIndex::new(&data, 100);
+            assert_eq!(index.crc32c(), crc32c(&data));
+            assert_eq!(index.blocks(), len.div_ceil(100));
+
+            for _ in 0..100 {
+                let a = rng.random_range(0..=len);
+                let b = rng.random_range(a..=len);
+
+                let crc = index.query(a as u64..b as u64, reader(&data)).unwrap();
+                assert_eq!(crc, crc32c(&data[a..b]), "{a}..{b} of {len}");
+            }
+
+            let whole = index.query(0..len as u64, reader(&data)).unwrap();
+            assert_eq!(whole, crc32c(&data));
+        }
+    }
+
+    #[test]
+    fn reads_only_edges() {
+        let mut data = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut data);
+
+        let index = Crc32cIndex::new(&data, 64);
+        let mut reads = Vec::new();
+
+        let crc = index
+            .query(10..900, |offset, buf| {
+                reads.push(offset..offset + buf.len() as u64);
+                reader(&data)(offset, buf)
+            })
+            .unwrap();
+
+        assert_eq!(crc, crc32c(&data[10..900]));
+        assert_eq!(reads, vec![10..64, 896..900]);
+    }
+
+    #[test]
+    fn can_update() {
+        let mut data = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut data);
+
+        let crcs: Vec<_> = data.chunks(64).map(crc32c).collect();
+        let mut index = Crc32cIndex::from_block_crcs(64, 1000, &crcs);
+        assert_eq!(index.crc32c(), crc32c(&data));
+
+        for block in [0, 7, 15] {
+            let start = block * 64;
+            let end = data.len().min(start + 64);
+            rand::rng().fill_bytes(&mut data[start..end]);
+
+            index.update(block, crc32c(&data[start..end]));
+            assert_eq!(index.block_crc32c(block), crc32c(&data[start..end]));
+            assert_eq!(index.crc32c(), crc32c(&data));
+            assert_eq!(
+                index.query(100..950, reader(&data)).unwrap(),
+                crc32c(&data[100..950])
+            );
+        }
+    }
+
+    #[test]
+    fn read_errors() {
+        let data = [0u8; 100];
+        let index = Crc32cIndex::new(&data, 10);
+
+        let result = index.query(5..50, |_, _| Err(io::ErrorKind::UnexpectedEof.into()));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        // Aligned ranges need no reads.
+        let result = index.query(10..50, |_, _| Err(io::ErrorKind::UnexpectedEof.into()));
+        assert_eq!(result.unwrap(), crc32c(&data[10..50]));
+    }
+}
@@ -1,12 +1,33 @@
 //! Provides wrappers for [Read] and [Write] types which checksum the bytes being read/written.
-use std::io::{Read, Write};
+use std::io::{self, BufRead, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
 
-use crate::crc32c_append;
+use crate::poly::Poly32;
+use crate::{crc32c_append, ChecksumMismatch, Crc32c};
+
+/// CRC-32C of any payload followed by its own CRC-32C, in little-endian order.
+const RESIDUE: u32 = 0x48_67_4B_C7;
 
 /// [Read]er wrapper which tracks the checksum of all bytes read.
+///
+/// Implements [BufRead] when the inner reader does, checksumming the bytes as they are consumed,
+/// and [Seek] when the inner reader does. Seeking to the start of the inner reader resets the
+/// checksum (to the seed, if any), and `SeekFrom::Current(0)` (e.g.
+/// [stream_position](Seek::stream_position)) leaves it untouched. Any other seek poisons the
+/// checksum, which then no longer matches the bytes read (see [Crc32cReader::is_poisoned]).
 pub struct Crc32cReader<R: Read> {
     checksum: u32,
+    len: u64,
+    seed: u32,
+    poisoned: bool,
     inner: R,
+    verify: Option<Verify>,
+    lookahead: Lookahead,
+}
+
+/// The expected checksum of a [Crc32cReader::verifying] reader.
+struct Verify {
+    expected: u32,
+    len: Option<u64>,
 }
 
 impl<R: Read> Crc32cReader<R> {
@@ -19,7 +40,39 @@ impl<R: Read> Crc32cReader<R> {
     pub fn new_with_seed(r: R, seed: u32) -> Self {
         Self {
             checksum: seed,
+            len: 0,
+            seed,
+            poisoned: false,
             inner: r,
+            verify: None,
+            lookahead: Lookahead::default(),
+        }
+    }
+
+    /// Wrap a [Read]er whose bytes must have the `expected` checksum.
+    ///
+    /// Once the inner reader reaches its end, reading returns an error of kind
+    /// [io::ErrorKind::InvalidData] wrapping a [ChecksumMismatch] if the checksum is wrong,
+    /// instead of signalling the end of the data. If a length is given, the read which reaches
+    /// `len` bytes returns this error instead, so that e.g. [Read::read_exact] fails, and if the
+    /// inner reader ends before `len` bytes, reading returns an error of kind
+    /// [io::ErrorKind::UnexpectedEof]. Bytes past `len` are left in the inner reader.
+    ///
+    /// If the checksum is poisoned by a seek, the end of the data is always an error.
+    pub fn verifying(r: R, expected: u32, len: Option<u64>) -> Self {
+        Self {
+            verify: Some(Verify { expected, len }),
+            ..Self::new(r)
+        }
+    }
+
+    /// Wrap a [Read]er positioned after the bytes covered by `state`, to resume a checksum
+    /// saved with [Crc32c::to_bytes].
+    pub fn resume(r: R, state: Crc32c) -> Self {
+        Self {
+            checksum: state.crc(),
+            len: state.len(),
+            ..Self::new(r)
         }
     }
 
@@ -32,19 +85,206 @@ impl<R: Read> Crc32cReader<R> {
     pub fn crc32c(&self) -> u32 {
         self.checksum
     }
+
+    /// Get the checksum of all bytes read, with their count.
+    ///
+    /// The count includes the bytes read through this wrapper and those covered by the state it
+    /// was resumed from, but not those covered by the seed.
+    pub fn digest(&self) -> Crc32c {
+        Crc32c::from_parts(self.checksum, self.len)
+    }
+
+    /// Returns whether a seek made the checksum meaningless.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    fn update(&mut self, buf: &[u8]) {
+        self.checksum = crc32c_append(self.checksum, buf);
+        self.len += buf.len() as u64;
+    }
+
+    /// Returns how many bytes a verifying reader with a length may still read.
+    fn remaining(&self) -> Option<u64> {
+        match self.verify {
+            Some(Verify { len: Some(len), .. }) => Some(len.saturating_sub(self.len)),
+            _ => None,
+        }
+    }
+
+    /// Checks the checksum at the end of the data of a verifying reader.
+    fn verify_end(&self) -> io::Result<()> {
+        match &self.verify {
+            Some(verify) => verify.check(self.checksum, self.len, self.poisoned),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Verify {
+    /// Checks the `checksum` of the `len` bytes read when the data ends.
+    fn check(&self, checksum: u32, len: u64, poisoned: bool) -> io::Result<()> {
+        if poisoned {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "CRC-32C cannot be verified after a seek",
+            ));
+        }
+
+        if self.len.is_some_and(|expected| len < expected) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "data ended before its expected length",
+            ));
+        }
+
+        if checksum != self.expected {
+            return Err(ChecksumMismatch {
+                expected: self.expected,
+                actual: checksum,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Limits a buffer length to the `remaining` bytes of a verifying reader.
+fn cap(len: usize, remaining: Option<u64>) -> usize {
+    match remaining {
+        Some(remaining) if remaining < len as u64 => remaining as usize,
+        _ => len,
+    }
 }
 
 impl<R: Read> Read for Crc32cReader<R> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let out = self.inner.read(buf)?;
-        self.checksum = crc32c_append(self.checksum, &buf[..out]);
+        self.lookahead.clear();
+
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let limit = cap(buf.len(), self.remaining());
+        if limit == 0 {
+            self.verify_end()?;
+            return Ok(0);
+        }
+
+        let out = self.inner.read(&mut buf[..limit])?;
+        if out == 0 {
+            self.verify_end()?;
+        }
+
+        self.update(&buf[..out]);
+        if self.remaining() == Some(0) {
+            self.verify_end()?;
+        }
+
+        Ok(out)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.lookahead.clear();
+
+        if self.remaining().is_some() {
+            // Only limit the length of a single buffer.
+            return match bufs.iter_mut().find(|buf| !buf.is_empty()) {
+                Some(buf) => self.read(buf),
+                None => Ok(0),
+            };
+        }
+
+        let out = self.inner.read_vectored(bufs)?;
+        if out == 0 && bufs.iter().any(|buf| !buf.is_empty()) {
+            self.verify_end()?;
+        }
+
+        self.checksum = append_prefix(self.checksum, bufs.iter().map(|buf| &**buf), out);
+        self.len += out as u64;
         Ok(out)
     }
 }
 
+impl<R: BufRead> BufRead for Crc32cReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let remaining = self.remaining();
+
+        if remaining == Some(0) {
+            self.verify_end()?;
+            return Ok(&[]);
+        }
+
+        // Borrow the fields separately, to check the end while the buffer is borrowed.
+        let Self {
+            checksum,
+            len,
+            poisoned,
+            inner,
+            verify,
+            lookahead,
+            ..
+        } = self;
+
+        let buf = inner.fill_buf()?;
+        if buf.is_empty() {
+            if let Some(verify) = verify {
+                verify.check(*checksum, *len, *poisoned)?;
+            }
+            return Ok(buf);
+        }
+
+        let buf = &buf[..cap(buf.len(), remaining)];
+
+        // Check the checksum before returning the last bytes, as consuming them cannot fail.
+        if let (Some(verify), Some(remaining)) = (verify, remaining) {
+            if buf.len() as u64 == remaining {
+                let checksum = crc32c_append(*checksum, buf);
+                verify.check(checksum, *len + remaining, *poisoned)?;
+            }
+        }
+
+        lookahead.filled(buf);
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let consumed = self.lookahead.consume(amt);
+        self.checksum = crc32c_append(self.checksum, consumed);
+        self.len += consumed.len() as u64;
+
+        self.inner.consume(amt);
+    }
+}
+
+impl<R: Read + Seek> Seek for Crc32cReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+
+        if position == 0 {
+            self.checksum = self.seed;
+            self.len = 0;
+            self.poisoned = false;
+        } else if pos != SeekFrom::Current(0) {
+            self.poisoned = true;
+        }
+
+        // Seeking discards the buffer of the inner reader.
+        self.lookahead.clear();
+        Ok(position)
+    }
+}
+
 /// [Write]r wrapper which tracks the checksum of all bytes written.
+///
+/// Only the bytes accepted by the inner writer are checksummed, including for vectored writes.
+/// Implements [Seek] when the inner writer does, with the same semantics as [Crc32cReader].
 pub struct Crc32cWriter<W: Write> {
     checksum: u32,
+    len: u64,
+    seed: u32,
+    poisoned: bool,
     inner: W,
 }
 
@@ -58,25 +298,76 @@ impl<W: Write> Crc32cWriter<W> {
     pub fn new_with_seed(w: W, seed: u32) -> Self {
         Self {
             checksum: seed,
+            len: 0,
+            seed,
+            poisoned: false,
             inner: w,
         }
     }
 
+    /// Wrap a [Write]r positioned after the bytes covered by `state`, to resume a checksum
+    /// saved with [Crc32c::to_bytes].
+    pub fn resume(w: W, state: Crc32c) -> Self {
+        Self {
+            checksum: state.crc(),
+            len: state.len(),
+            ..Self::new(w)
+        }
+    }
+
     /// Unwrap the inner [Write]r.
     pub fn into_inner(self) -> W {
         self.inner
     }
 
+    /// Write the checksum as a 4-byte little-endian trailer, flush, and unwrap the inner
+    /// [Write]r.
+    ///
+    /// The trailer is not included in the checksum. Use a [TrailerReader] to read the payload
+    /// back and verify it. Returns an error if the checksum was poisoned by a seek.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.poisoned {
+            return Err(io::Error::other(
+                "CRC-32C trailer cannot be written after a seek",
+            ));
+        }
+
+        self.inner.write_all(&self.checksum.to_le_bytes())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
     /// Get the checksum of all bytes written.
     pub fn crc32c(&self) -> u32 {
         self.checksum
     }
+
+    /// Get the checksum of all bytes written, with their count.
+    ///
+    /// The count includes the bytes written through this wrapper and those covered by the state
+    /// it was resumed from, but not those covered by the seed.
+    pub fn digest(&self) -> Crc32c {
+        Crc32c::from_parts(self.checksum, self.len)
+    }
+
+    /// Returns whether a seek made the checksum meaningless.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
 }
 
 impl<W: Write> Write for Crc32cWriter<W> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         let out = self.inner.write(buf)?;
         self.checksum = crc32c_append(self.checksum, &buf[..out]);
+        self.len += out as u64;
+        Ok(out)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let out = self.inner.write_vectored(bufs)?;
+        self.checksum = append_prefix(self.checksum, bufs.iter().map(|buf| &**buf), out);
+        self.len += out as u64;
         Ok(out)
     }
 
@@ -85,10 +376,201 @@ impl<W: Write> Write for Crc32cWriter<W> {
     }
 }
 
+impl<W: Write + Seek> Seek for Crc32cWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+
+        if position == 0 {
+            self.checksum = self.seed;
+            self.len = 0;
+            self.poisoned = false;
+        } else if pos != SeekFrom::Current(0) {
+            self.poisoned = true;
+        }
+
+        Ok(position)
+    }
+}
+
+/// [Read]er wrapper for data followed by its checksum, as a 4-byte little-endian trailer.
+///
+/// Only the payload is returned: the last 4 bytes read from the inner reader are held back
+/// until its end. The checksum of a payload followed by its own checksum is a constant (the
+/// residue), so the whole data is checksummed as it is read, and the trailer is checked without
+/// any more lookahead. At the end, reading returns an error of kind [io::ErrorKind::InvalidData]
+/// wrapping a [ChecksumMismatch] if the trailer does not match, or of kind
+/// [io::ErrorKind::UnexpectedEof] if there are fewer than 4 bytes.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::{Read, Write};
+/// use crc32c::{Crc32cWriter, TrailerReader};
+///
+/// let mut writer = Crc32cWriter::new(Vec::new());
+/// writer.write_all(b"Hello world!").unwrap();
+/// let framed = writer.finish().unwrap();
+///
+/// let mut payload = Vec::new();
+/// TrailerReader::new(&framed[..]).read_to_end(&mut payload).unwrap();
+///
+/// assert_eq!(payload, b"Hello world!");
+/// ```
+pub struct TrailerReader<R: Read> {
+    /// Checksum of all bytes read from the inner reader, including the held back ones.
+    checksum: u32,
+    held: [u8; 4],
+    held_len: usize,
+    inner: R,
+}
+
+impl<R: Read> TrailerReader<R> {
+    /// Wrap an instance of a [Read]er.
+    pub fn new(r: R) -> Self {
+        Self {
+            checksum: 0,
+            held: [0; 4],
+            held_len: 0,
+            inner: r,
+        }
+    }
+
+    /// Unwrap the inner [Read]er.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Checks the trailer at the end of the inner reader.
+    fn verify_end(&self) -> io::Result<usize> {
+        if self.held_len < 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "data ended before its checksum trailer",
+            ));
+        }
+
+        if self.checksum == RESIDUE {
+            return Ok(0);
+        }
+
+        // Appending the trailer `t` to a payload of checksum `c` shifts `!c ^ t` past 32 zero
+        // bits, which can be undone to report the checksum of the payload.
+        let expected = u32::from_le_bytes(self.held);
+        let shifted = Poly32::from_register(!self.checksum);
+        let inverse = Poly32::x_pow(32).inverse().expect("x is invertible");
+        let actual = !((shifted * inverse).to_register() ^ expected);
+
+        Err(ChecksumMismatch { expected, actual }.into())
+    }
+}
+
+impl<R: Read> Read for TrailerReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return self.verify_end();
+            }
+            self.checksum = crc32c_append(self.checksum, &buf[..n]);
+
+            let held = self.held_len;
+            if held + n <= 4 {
+                self.held[held..held + n].copy_from_slice(&buf[..n]);
+                self.held_len += n;
+                continue;
+            }
+
+            // Return all but the last 4 bytes of the held bytes followed by the new ones.
+            let out = held + n - 4;
+            let mut tail = [0; 4];
+            if n >= 4 {
+                tail.copy_from_slice(&buf[n - 4..n]);
+                buf.copy_within(..n - 4, held);
+                buf[..held].copy_from_slice(&self.held[..held]);
+            } else {
+                tail[..4 - n].copy_from_slice(&self.held[out..held]);
+                tail[4 - n..].copy_from_slice(&buf[..n]);
+                buf[..out].copy_from_slice(&self.held[..out]);
+            }
+
+            self.held = tail;
+            self.held_len = 4;
+            return Ok(out);
+        }
+    }
+}
+
+/// Appends the first `n` bytes of a sequence of buffers to a checksum, e.g. after a vectored
+/// read or write.
+pub(crate) fn append_prefix<'a>(
+    mut crc: u32,
+    bufs: impl IntoIterator<Item = &'a [u8]>,
+    mut n: usize,
+) -> u32 {
+    for buf in bufs {
+        if n == 0 {
+            break;
+        }
+
+        let len = buf.len().min(n);
+        crc = crc32c_append(crc, &buf[..len]);
+        n -= len;
+    }
+
+    crc
+}
+
+/// The bytes returned by the last `fill_buf` of a buffered reader which were not consumed yet.
+///
+/// They are checksummed when they are consumed, without fetching them from the inner reader
+/// again, which could fail. Each byte is only copied once, as the unconsumed bytes are returned
+/// again at the start of the next `fill_buf`.
+#[derive(Default)]
+pub(crate) struct Lookahead {
+    buf: Vec<u8>,
+    start: usize,
+}
+
+impl Lookahead {
+    /// Records the buffer returned by `fill_buf`.
+    pub(crate) fn filled(&mut self, buf: &[u8]) {
+        let known = self.buf.len() - self.start;
+
+        if buf.len() <= known {
+            self.buf.truncate(self.start + buf.len());
+            return;
+        }
+
+        self.buf.drain(..self.start);
+        self.start = 0;
+        self.buf.extend_from_slice(&buf[known..]);
+    }
+
+    /// Returns the bytes being consumed, which are at most those returned by `fill_buf`.
+    pub(crate) fn consume(&mut self, amt: usize) -> &[u8] {
+        let start = self.start;
+        self.start += amt.min(self.buf.len() - start);
+
+        &self.buf[start..self.start]
+    }
+
+    /// Forgets the buffer, after the inner reader was read or seeked directly.
+    pub(crate) fn clear(&mut self) {
+        self.buf.clear();
+        self.start = 0;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
 
+    use rand::{Rng, RngCore};
+
     use super::*;
 
     const TEST_STRING: &[u8] =
@@ -103,6 +585,7 @@ mod tests {
         assert_eq!(n_read, TEST_STRING.len());
         assert_eq!(buf.as_slice(), TEST_STRING);
         assert_eq!(reader.crc32c(), CHECKSUM);
+        assert_eq!(reader.digest(), Crc32c::new(TEST_STRING));
     }
 
     #[test]
@@ -112,8 +595,329 @@ mod tests {
         let mut writer = Crc32cWriter::<Cursor<&mut Vec<u8>>>::new(Cursor::new(&mut buf));
         writer.write_all(TEST_STRING).unwrap();
         let checksum = writer.crc32c();
+        let digest = writer.digest();
 
         assert_eq!(buf.as_slice(), TEST_STRING);
         assert_eq!(checksum, CHECKSUM);
+        assert_eq!(digest, Crc32c::new(TEST_STRING));
+    }
+
+    #[test]
+    fn can_verify() {
+        let mut reader = Crc32cReader::verifying(TEST_STRING, CHECKSUM, None);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, TEST_STRING);
+
+        let mut reader = Crc32cReader::verifying(TEST_STRING, CHECKSUM ^ 1, None);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mismatch = err.into_inner().unwrap().downcast::<ChecksumMismatch>();
+        assert_eq!(
+            *mismatch.unwrap(),
+            ChecksumMismatch {
+                expected: CHECKSUM ^ 1,
+                actual: CHECKSUM
+            }
+        );
+
+        // The error is repeated, rather than followed by the end of the data.
+        let err = reader.read(&mut [0; 10]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn can_verify_len() {
+        let (head, tail) = TEST_STRING.split_at(20);
+        let expected = crate::crc32c(head);
+
+        let mut reader = Crc32cReader::verifying(TEST_STRING, expected, Some(20));
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, head);
+        assert_eq!(reader.into_inner(), tail);
+
+        let mut reader = Crc32cReader::verifying(TEST_STRING, expected ^ 1, Some(20));
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut reader = Crc32cReader::verifying(head, expected, Some(21));
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        // The read which reaches the length fails, without waiting for another read.
+        let mut buf = [0; 20];
+        let mut reader = Crc32cReader::verifying(TEST_STRING, expected, Some(20));
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, head);
+
+        let mut reader = Crc32cReader::verifying(TEST_STRING, expected ^ 1, Some(20));
+        let err = reader.read_exact(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let inner = io::BufReader::with_capacity(7, TEST_STRING);
+        let mut reader = Crc32cReader::verifying(inner, expected ^ 1, Some(20));
+        reader.fill_buf().unwrap();
+        reader.consume(7);
+        reader.fill_buf().unwrap();
+        reader.consume(7);
+        let err = reader.fill_buf().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn residue() {
+        for payload in [&b""[..], b"a", TEST_STRING] {
+            let crc = crate::crc32c(payload);
+            let framed = [payload, &crc.to_le_bytes()].concat();
+
+            assert_eq!(crate::crc32c(&framed), RESIDUE);
+        }
+    }
+
+    #[test]
+    fn can_frame() {
+        let mut writer = Crc32cWriter::new(Vec::new());
+        writer.write_all(TEST_STRING).unwrap();
+        let framed = writer.finish().unwrap();
+        assert_eq!(framed[TEST_STRING.len()..], CHECKSUM.to_le_bytes());
+
+        let mut buf = Vec::new();
+        TrailerReader::new(&framed[..])
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, TEST_STRING);
+
+        // Small reads.
+        for size in 1..6 {
+            let mut reader = TrailerReader::new(&framed[..]);
+            let mut buf = Vec::new();
+            let mut chunk = vec![0; size];
+
+            loop {
+                let n = reader.read(&mut chunk).unwrap();
+                if n == 0 {
+                    break;
+                }
+                buf.extend_from_slice(&chunk[..n]);
+            }
+
+            assert_eq!(buf, TEST_STRING, "{}", size);
+        }
+    }
+
+    #[test]
+    fn detects_bad_trailer() {
+        let mut framed = TEST_STRING.to_vec();
+        framed.extend_from_slice(&CHECKSUM.to_le_bytes());
+        framed[10] ^= 1;
+
+        let err = TrailerReader::new(&framed[..])
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mismatch = err.into_inner().unwrap().downcast::<ChecksumMismatch>();
+        assert_eq!(
+            *mismatch.unwrap(),
+            ChecksumMismatch {
+                expected: CHECKSUM,
+                actual: crate::crc32c(&framed[..TEST_STRING.len()])
+            }
+        );
+
+        let err = TrailerReader::new(&framed[..3])
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    /// A writer which accepts at most 5 bytes per call, across buffers for vectored writes.
+    struct Trickle(Vec<u8>);
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.write_vectored(&[IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+            let mut n = 0;
+
+            for buf in bufs {
+                let len = buf.len().min(5 - n);
+                self.0.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn can_buf_read() {
+        let mut reader = Crc32cReader::new(io::BufReader::with_capacity(7, TEST_STRING));
+        let mut word = Vec::new();
+
+        reader.read_until(b' ', &mut word).unwrap();
+        assert_eq!(word, b"This ");
+        assert_eq!(reader.digest(), Crc32c::new(b"This "));
+
+        reader.read_to_end(&mut word).unwrap();
+        assert_eq!(word, TEST_STRING);
+        assert_eq!(reader.crc32c(), CHECKSUM);
+
+        let (head, _) = TEST_STRING.split_at(20);
+        let inner = io::BufReader::with_capacity(7, TEST_STRING);
+        let mut reader = Crc32cReader::verifying(inner, crate::crc32c(head), Some(20));
+        let mut buf = Vec::new();
+        reader.read_until(b'!', &mut buf).unwrap();
+        assert_eq!(buf, head);
+
+        let inner = io::BufReader::with_capacity(7, TEST_STRING);
+        let mut reader = Crc32cReader::verifying(inner, CHECKSUM ^ 1, None);
+        let err = reader.read_until(b'!', &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// A buffered reader which fails when its buffer is filled again before being consumed.
+    struct Strict<'a> {
+        data: &'a [u8],
+        filled: bool,
+    }
+
+    impl Read for Strict<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.data.read(buf)
+        }
+    }
+
+    impl BufRead for Strict<'_> {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            if self.filled {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+
+            self.filled = true;
+            Ok(&self.data[..self.data.len().min(7)])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.filled = false;
+            self.data = &self.data[amt..];
+        }
+    }
+
+    #[test]
+    fn consumes_last_buffer() {
+        let inner = Strict {
+            data: TEST_STRING,
+            filled: false,
+        };
+        let mut reader = Crc32cReader::new(inner);
+
+        let mut word = Vec::new();
+        reader.read_until(b' ', &mut word).unwrap();
+        assert_eq!(reader.digest(), Crc32c::new(b"This "));
+
+        // Reading directly skips the rest of the buffer.
+        let mut reader = Crc32cReader::new(io::BufReader::with_capacity(7, TEST_STRING));
+        reader.fill_buf().unwrap();
+        reader.read_exact(&mut [0; 10]).unwrap();
+        reader.fill_buf().unwrap();
+        reader.consume(3);
+        assert_eq!(reader.digest(), Crc32c::new(&TEST_STRING[..13]));
+
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(reader.crc32c(), CHECKSUM);
+    }
+
+    #[test]
+    fn vectored() {
+        let mut reader = Crc32cReader::new(TEST_STRING);
+        let (mut a, mut b) = ([0; 3], [0; 10]);
+        let n = reader
+            .read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+            .unwrap();
+        assert_eq!(n, 13);
+        assert_eq!(reader.digest(), Crc32c::new(&TEST_STRING[..13]));
+
+        let mut writer = Crc32cWriter::new(Trickle(Vec::new()));
+        let (a, b) = TEST_STRING.split_at(3);
+        let n = writer
+            .write_vectored(&[IoSlice::new(a), IoSlice::new(b)])
+            .unwrap();
+        assert_eq!(n, 5);
+        assert_eq!(writer.digest(), Crc32c::new(&TEST_STRING[..5]));
+
+        writer.write_all(&TEST_STRING[5..]).unwrap();
+        assert_eq!(writer.crc32c(), CHECKSUM);
+        assert_eq!(writer.into_inner().0, TEST_STRING);
+    }
+
+    #[test]
+    fn seek() {
+        let mut reader = Crc32cReader::new(Cursor::new(TEST_STRING));
+        reader.read_exact(&mut [0; 10]).unwrap();
+
+        assert_eq!(reader.stream_position().unwrap(), 10);
+        assert_eq!(reader.digest(), Crc32c::new(&TEST_STRING[..10]));
+        assert!(!reader.is_poisoned());
+
+        reader.seek(SeekFrom::Current(5)).unwrap();
+        assert!(reader.is_poisoned());
+
+        reader.rewind().unwrap();
+        assert!(!reader.is_poisoned());
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(reader.crc32c(), CHECKSUM);
+
+        let mut reader = Crc32cReader::verifying(Cursor::new(TEST_STRING), CHECKSUM, None);
+        reader.seek(SeekFrom::Start(1)).unwrap();
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut writer = Crc32cWriter::new(Cursor::new(Vec::new()));
+        writer.write_all(b"garbage").unwrap();
+        writer.rewind().unwrap();
+        writer.write_all(TEST_STRING).unwrap();
+        assert_eq!(writer.digest(), Crc32c::new(TEST_STRING));
+
+        writer.seek(SeekFrom::End(-1)).unwrap();
+        assert!(writer.is_poisoned());
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn can_resume() {
+        let mut data = vec![0u8; 10_000];
+        rand::rng().fill_bytes(&mut data);
+
+        for _ in 0..20 {
+            let offset = rand::rng().random_range(0..=data.len());
+
+            let mut reader = Crc32cReader::new(&data[..]);
+            reader.read_exact(&mut vec![0; offset]).unwrap();
+            let state = reader.digest().to_bytes().unwrap();
+
+            let state = Crc32c::from_bytes(state).unwrap();
+            let mut reader = Crc32cReader::resume(&data[offset..], state);
+            reader.read_to_end(&mut Vec::new()).unwrap();
+            assert_eq!(reader.digest(), Crc32c::new(&data));
+
+            let mut writer = Crc32cWriter::new(Vec::new());
+            writer.write_all(&data[..offset]).unwrap();
+            let state = writer.digest().to_bytes().unwrap();
+
+            let state = Crc32c::from_bytes(state).unwrap();
+            let mut writer = Crc32cWriter::resume(Vec::new(), state);
+            writer.write_all(&data[offset..]).unwrap();
+            assert_eq!(writer.digest(), Crc32c::new(&data));
+            assert_eq!(writer.into_inner(), &data[offset..]);
+        }
     }
 }
@@ -19,22 +19,81 @@
 //!
 //! Otherwise, the crate will use `cpuid` at runtime to detect the
 //! running CPU's features, and enable the appropriate algorithm.
+//!
+//! # Optional features
+//!
+//! - `tokio`, `futures-io`: `AsyncCrc32cReader` and `AsyncCrc32cWriter`, which implement the
+//!   asynchronous I/O traits of `tokio` and `futures` respectively.
+//! - `futures`: `Crc32cStream` and `Crc32cSink`, which checksum streams and sinks of byte
+//!   chunks.
+//! - `bytes`: `crc32c_buf`, `Crc32cBuf` and `Crc32cBufMut`, which checksum the `Buf` and
+//!   `BufMut` types of `bytes`.
+//! - `mmap`: lets [file::checksum_path] map large files into memory rather than read them.
+//! - `sparse`: lets [file::checksum_path] skip the holes of sparse files on Linux.
 
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+pub mod analysis;
+mod assembler;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+mod async_io;
+#[cfg(feature = "bytes")]
+mod buf;
+mod chunker;
 mod combine;
+mod correct;
+pub mod delta;
+mod digest;
+pub mod file;
+pub mod generic;
 mod hasher;
 #[cfg(all(target_arch = "aarch64", armsimd))]
 mod hw_aarch64;
-#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
 mod hw_tables;
 #[cfg(target_arch = "x86_64")]
 mod hw_x86_64;
+mod index;
 mod io;
+pub mod poly;
+mod rolling;
+mod shift;
+#[cfg(feature = "futures")]
+mod stream;
 mod sw;
+mod tables;
 mod util;
+mod variant;
+mod vectored;
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+pub use async_io::{AsyncCrc32cReader, AsyncCrc32cWriter};
+
+pub use assembler::{Crc32cAssembler, SegmentError};
+
+#[cfg(feature = "bytes")]
+pub use buf::{crc32c_buf, Crc32cBuf, Crc32cBufMut};
+
+pub use chunker::{Chunk, Chunker, ReadChunks, SliceChunks};
+
+pub use correct::{crc32c_correct, Correction, Uncorrectable};
+
+pub use digest::{ChecksumMismatch, Crc32c, LengthOverflow, UnknownVersion};
 
 pub use hasher::Crc32cHasher;
 
-pub use io::{Crc32cReader, Crc32cWriter};
+pub use index::Crc32cIndex;
+
+pub use io::{Crc32cReader, Crc32cWriter, TrailerReader};
+
+pub use rolling::Crc32cRolling;
+
+pub use shift::Crc32cShift;
+
+#[cfg(feature = "futures")]
+pub use stream::{Crc32cHandle, Crc32cSink, Crc32cStream};
+
+pub use variant::{detect_variant, ByteOrder, Variant};
 
 /// Computes the CRC for the data payload.
 ///
@@ -47,21 +106,92 @@ pub fn crc32c(data: &[u8]) -> u32 {
 /// Computes the CRC for the data payload, starting with a previous CRC value.
 #[inline]
 pub fn crc32c_append(crc: u32, data: &[u8]) -> u32 {
+    !crc32c_raw_update(!crc, data)
+}
+
+/// Updates a raw CRC register with the data payload.
+///
+/// Unlike [crc32c_append], the register is not inverted on entry and exit, and the caller
+/// controls the seed. This is the convention of the Linux kernel's `crc32c()` and of Ceph's
+/// `ceph_crc32c()`, which formats such as btrfs, ext4 and Ceph's messenger build on: those
+/// usually seed with `!0`, and some invert the result themselves.
+///
+/// `crc32c_raw_update(!0, data)` is equal to `!crc32c(data)`.
+#[inline]
+pub fn crc32c_raw_update(crc: u32, data: &[u8]) -> u32 {
     #[cfg(target_arch = "x86_64")]
     {
         if is_x86_feature_detected!("sse4.2") {
-            return unsafe { hw_x86_64::crc32c(crc, data) };
+            return unsafe { hw_x86_64::crc32c_raw(crc, data) };
         }
     }
 
     #[cfg(all(target_arch = "aarch64", armsimd))]
     {
         if std::arch::is_aarch64_feature_detected!("crc") {
-            return unsafe { hw_aarch64::crc32c(crc, data) };
+            return unsafe { hw_aarch64::crc32c_raw(crc, data) };
         }
     }
 
-    sw::crc32c(crc, data)
+    sw::crc32c_raw(crc, data)
+}
+
+/// Computes the CRC for the concatenation of several buffers, e.g. a list of
+/// [IoSlice](std::io::IoSlice)s.
+///
+/// Small buffers are gathered before being hashed, and the words split across the boundaries of
+/// large buffers are stitched together, so that fragments do not lose the hardware-optimized
+/// path.
+pub fn crc32c_vectored<T: Deref<Target = [u8]>>(bufs: &[T]) -> u32 {
+    let mut stitcher = vectored::Stitcher::new(!0);
+    for buf in bufs {
+        stitcher.update(buf);
+    }
+    !stitcher.finish()
+}
+
+/// Computes the CRC for the contents of a ring buffer, in order.
+pub fn crc32c_deque(deque: &VecDeque<u8>) -> u32 {
+    let (front, back) = deque.as_slices();
+    crc32c_vectored(&[front, back])
+}
+
+/// Computes the CRC for `times` repetitions of the `pattern`, without materializing them.
+///
+/// Equivalent to calling `crc32c_append_repeat(0, pattern, times)`.
+#[inline]
+pub fn crc32c_repeat(pattern: &[u8], times: u64) -> u32 {
+    crc32c_append_repeat(0, pattern, times)
+}
+
+/// Computes the CRC for `times` repetitions of the `pattern`, starting with a previous CRC value.
+///
+/// This takes `O(pattern.len() + log(times))` time, by repeatedly doubling the pattern with
+/// the same operator as [crc32c_combine].
+#[inline]
+pub fn crc32c_append_repeat(crc: u32, pattern: &[u8], times: u64) -> u32 {
+    combine::crc32c_append_repeat(crc, pattern, times)
+}
+
+/// Computes the CRC for the first `bit_len` bits of the data payload, starting with a previous
+/// CRC value.
+///
+/// CRC-32C is a reflected CRC: the bits of each byte are consumed least significant bit first.
+/// When `bit_len` is not a multiple of 8, the message ends with the `bit_len % 8` low-order bits
+/// of the last byte, and its high-order bits are ignored.
+///
+/// # Panics
+///
+/// Panics if `bit_len` is greater than `8 * data.len()`.
+pub fn crc32c_append_bits(crc: u32, data: &[u8], bit_len: usize) -> u32 {
+    let (bytes, bits) = (bit_len / 8, bit_len % 8);
+    let crc = crc32c_append(crc, &data[..bytes]);
+
+    if bits == 0 {
+        return crc;
+    }
+
+    !sw::crc_bits(!crc, data[bytes], bits as u32)
 }
 
 /// Computes the "combined" value of two CRC32c values. Specifically, given two byte streams A and
@@ -69,5 +199,25 @@ pub fn crc32c_append(crc: u32, data: &[u8]) -> u32 {
 /// using only crc32c(A), crc32c(B), and the length of B.
 #[inline]
 pub fn crc32c_combine(crc1: u32, crc2: u32, len2: usize) -> u32 {
-    combine::crc32c_combine(crc1, crc2, len2)
+    combine::crc32c_combine(crc1, crc2, len2 as u64)
+}
+
+/// Computes the "combined" value of two raw CRC registers, given the length of the second byte
+/// stream.
+///
+/// `crc1` may have been computed with any seed, but `crc2` must have been computed from a zero
+/// seed, i.e. with `crc32c_raw_update(0, b)`. The result is then the register after updating
+/// `crc1` with both byte streams, as in the Linux kernel's `crc32c_combine()`.
+#[inline]
+pub fn crc32c_raw_combine(crc1: u32, crc2: u32, len2: usize) -> u32 {
+    // The inversions of crc32c_combine cancel out, so both share the same operator.
+    combine::crc32c_combine(crc1, crc2, len2 as u64)
+}
+
+/// Same as [crc32c_combine], but with the length of the second byte stream given in bits.
+///
+/// Use this to combine the CRCs of messages computed with [crc32c_append_bits].
+#[inline]
+pub fn crc32c_combine_bits(crc1: u32, crc2: u32, len2_bits: u64) -> u32 {
+    combine::crc32c_combine_bits(crc1, crc2, len2_bits)
 }
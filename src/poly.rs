@@ -0,0 +1,291 @@
+//! Polynomial arithmetic over GF(2), modulo the CRC-32C polynomial.
+//!
+//! A CRC is the remainder of the message, viewed as a polynomial over GF(2), divided by the
+//! CRC polynomial. Appending `n` zero bits to a message multiplies its remainder by `x^n`, which
+//! is what makes [crc32c_combine](crate::crc32c_combine) possible:
+//!
+//! ```rust
+//! use crc32c::poly::Poly32;
+//!
+//! let a = crc32c::crc32c(b"Hello ");
+//! let b = crc32c::crc32c(b"world!");
+//!
+//! let shifted = Poly32::x_pow(8 * 6) * Poly32::from_register(a);
+//!
+//! assert_eq!(shifted.to_register() ^ b, crc32c::crc32c(b"Hello world!"));
+//! ```
+//!
+//! The multiplication and `x^(2^k)` table follow zlib's `multmodp` and `x2nmodp`.
+//!
+//! This file is based on the Zlib project, located at: <https://github.com/madler/zlib>,
+//! which includes the following notice:
+//!
+//! crc32.c -- compute the CRC-32 of a data stream
+//! Copyright (C) 1995-2022 Mark Adler
+//! For conditions of distribution and use, see copyright notice in zlib.h
+
+use std::ops;
+
+/// CRC-32-Castagnoli polynomial in reversed bit order.
+pub const POLYNOMIAL: u32 = 0x82_F6_3B_78;
+
+/// A polynomial over GF(2) of degree less than 32, reduced modulo the CRC-32C polynomial.
+///
+/// The coefficients are stored in the same reflected bit order as the CRC register: the most
+/// significant bit holds the coefficient of `x^0`, the least significant bit that of `x^31`.
+/// Addition is XOR, and multiplication is carry-less multiplication followed by reduction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Poly32(u32);
+
+/// `x^(2^k) mod P`, for `k` in `0..64`.
+const X2N_TABLE: [Poly32; 64] = {
+    let mut table = [Poly32::ZERO; 64];
+    let mut p = Poly32::X;
+    let mut k = 0;
+
+    while k < 64 {
+        table[k] = p;
+        p = Poly32(multiply_mod(p.0, p.0, POLYNOMIAL));
+        k += 1;
+    }
+
+    table
+};
+
+impl Poly32 {
+    /// The zero polynomial.
+    pub const ZERO: Self = Self(0);
+
+    /// The polynomial `1`, the multiplicative identity.
+    pub const ONE: Self = Self(1 << 31);
+
+    /// The polynomial `x`.
+    pub const X: Self = Self(1 << 30);
+
+    /// Interpret the value of a CRC register as a polynomial.
+    pub const fn from_register(crc: u32) -> Self {
+        Self(crc)
+    }
+
+    /// Get the value of a CRC register holding this polynomial.
+    pub const fn to_register(self) -> u32 {
+        self.0
+    }
+
+    /// Computes `x^n mod P`.
+    ///
+    /// This is the operator which shifts a CRC past `n` zero bits.
+    pub fn x_pow(mut n: u64) -> Self {
+        let mut result = Self::ONE;
+        let mut k = 0;
+
+        while n != 0 {
+            if n & 1 != 0 {
+                result *= X2N_TABLE[k];
+            }
+            n >>= 1;
+            k += 1;
+        }
+
+        result
+    }
+
+    /// Computes `self^n mod P`.
+    pub fn pow(self, n: u64) -> Self {
+        Self(pow_mod(self.0, n, POLYNOMIAL))
+    }
+
+    /// Computes the multiplicative inverse modulo P.
+    ///
+    /// The CRC-32C polynomial is not irreducible (it has `x + 1` as a factor), so only the
+    /// polynomials which share no factor with it have an inverse. Returns [None] for the others.
+    pub fn inverse(self) -> Option<Self> {
+        // Extended Euclidean algorithm, in the natural bit order (bit i holds x^i).
+        let modulus = (1 << 32) | u64::from(POLYNOMIAL.reverse_bits());
+        let (mut r0, mut r1) = (modulus, u64::from(self.0.reverse_bits()));
+        let (mut s0, mut s1) = (0, 1);
+
+        while r1 != 0 {
+            let (q, r) = divide(r0, r1);
+            r0 = r1;
+            r1 = r;
+
+            let s = s0 ^ carryless_multiply(q, s1);
+            s0 = s1;
+            s1 = s;
+        }
+
+        if r0 == 1 {
+            Some(Self((s0 as u32).reverse_bits()))
+        } else {
+            None
+        }
+    }
+}
+
+impl ops::Add for Poly32 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 ^ rhs.0)
+    }
+}
+
+impl ops::AddAssign for Poly32 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::Mul for Poly32 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(multiply_mod(self.0, rhs.0, POLYNOMIAL))
+    }
+}
+
+impl ops::MulAssign for Poly32 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+/// Multiplies `a` and `b` modulo a `polynomial`, all in the reflected bit order.
+pub(crate) const fn multiply_mod(a: u32, mut b: u32, polynomial: u32) -> u32 {
+    let mut m = 1 << 31;
+    let mut p = 0;
+
+    loop {
+        if a & m != 0 {
+            p ^= b;
+            if a & (m - 1) == 0 {
+                break;
+            }
+        }
+
+        m >>= 1;
+
+        if m == 0 {
+            break;
+        }
+
+        b = if b & 1 != 0 {
+            (b >> 1) ^ polynomial
+        } else {
+            b >> 1
+        };
+    }
+
+    p
+}
+
+/// Computes `a^n` modulo a `polynomial`, all in the reflected bit order.
+pub(crate) fn pow_mod(a: u32, mut n: u64, polynomial: u32) -> u32 {
+    let mut result = Poly32::ONE.0;
+    let mut square = a;
+
+    while n != 0 {
+        if n & 1 != 0 {
+            result = multiply_mod(result, square, polynomial);
+        }
+        square = multiply_mod(square, square, polynomial);
+        n >>= 1;
+    }
+
+    result
+}
+
+/// Multiplies `a` and `b` without reduction, in the natural bit order.
+///
+/// The caller must make sure the product has a degree below 64.
+fn carryless_multiply(mut a: u64, b: u64) -> u64 {
+    let mut p = 0;
+    let mut shift = 0;
+
+    while a != 0 {
+        if a & 1 != 0 {
+            p ^= b << shift;
+        }
+        a >>= 1;
+        shift += 1;
+    }
+
+    p
+}
+
+/// Divides `a` by `b`, returning the quotient and remainder, in the natural bit order.
+fn divide(mut a: u64, b: u64) -> (u64, u64) {
+    let degree = 63 - b.leading_zeros();
+    let mut q = 0;
+
+    while a != 0 && 63 - a.leading_zeros() >= degree {
+        let shift = 63 - a.leading_zeros() - degree;
+        q |= 1 << shift;
+        a ^= b << shift;
+    }
+
+    (q, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    fn random() -> Poly32 {
+        Poly32::from_register(rand::rng().next_u32())
+    }
+
+    #[test]
+    fn x_pow() {
+        let mut p = Poly32::ONE;
+
+        for n in 0..100 {
+            assert_eq!(Poly32::x_pow(n), p);
+            p *= Poly32::X;
+        }
+
+        assert_eq!(Poly32::x_pow(32), Poly32::from_register(POLYNOMIAL));
+        assert_eq!(Poly32::x_pow(12345), Poly32::X.pow(12345));
+    }
+
+    #[test]
+    fn arithmetic() {
+        for _ in 0..100 {
+            let (a, b, c) = (random(), random(), random());
+
+            assert_eq!(a * Poly32::ONE, a);
+            assert_eq!(a * b, b * a);
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!(a.pow(3), a * a * a);
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(Poly32::ZERO.inverse(), None);
+        assert_eq!(Poly32::ONE.inverse(), Some(Poly32::ONE));
+
+        // x + 1 divides the CRC-32C polynomial.
+        assert_eq!((Poly32::X + Poly32::ONE).inverse(), None);
+
+        for _ in 0..100 {
+            let a = random();
+
+            // Polynomials with an even number of terms are divisible by x + 1.
+            let odd_terms = a.to_register().count_ones() % 2 == 1;
+            assert_eq!(a.inverse().is_some(), odd_terms);
+
+            if let Some(inverse) = a.inverse() {
+                assert_eq!(a * inverse, Poly32::ONE);
+            }
+        }
+
+        let x = Poly32::X.inverse().unwrap();
+        assert_eq!(x * Poly32::x_pow(100), Poly32::x_pow(99));
+    }
+}
@@ -0,0 +1,147 @@
+//! Provides a CRC-32C over a sliding window of fixed size.
+use crate::combine::zeros_operator;
+use crate::poly::Poly32;
+use crate::{crc32c, crc32c_append};
+
+/// A CRC-32C over the last `window` bytes of a stream, updated in `O(1)` per byte.
+///
+/// Appending a byte `b` to the window `o || rest` gives the CRC of `o || rest || b`, and
+/// [crc32c_combine](crate::crc32c_combine) shows that removing `o` from the front only adds
+/// `crc32c(&[o])` shifted past `window` bytes. Those shifted values are precomputed for the 256
+/// possible bytes.
+///
+/// # Example
+///
+/// ```rust
+/// use crc32c::Crc32cRolling;
+///
+/// let mut rolling = Crc32cRolling::new(12);
+///
+/// for &byte in b">>> Hello world!" {
+///     rolling.push(byte);
+/// }
+///
+/// assert_eq!(rolling.crc32c(), crc32c::crc32c(b"Hello world!"));
+/// ```
+#[derive(Clone)]
+pub struct Crc32cRolling {
+    /// `crc32c(&[o])` shifted past `window` zero bytes, for every byte `o`.
+    table: Box<[u32; 256]>,
+    /// The bytes of the window, as a ring buffer.
+    buf: Box<[u8]>,
+    /// Index of the oldest byte in `buf`, once the window is full.
+    pos: usize,
+    len: usize,
+    crc: u32,
+}
+
+impl Crc32cRolling {
+    /// Create a rolling CRC over a window of `window` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "window must not be empty");
+
+        let op = zeros_operator(window as u64);
+        let mut table = Box::new([0; 256]);
+        for (byte, entry) in table.iter_mut().enumerate() {
+            let crc = Poly32::from_register(crc32c(&[byte as u8]));
+            *entry = (op * crc).to_register();
+        }
+
+        Self {
+            table,
+            buf: vec![0; window].into_boxed_slice(),
+            pos: 0,
+            len: 0,
+            crc: 0,
+        }
+    }
+
+    /// Get the size of the window.
+    pub fn window(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns whether `window` bytes were pushed since the creation or the last reset.
+    pub fn is_full(&self) -> bool {
+        self.len == self.buf.len()
+    }
+
+    /// Push a byte into the window, dropping the oldest one if the window is full. Returns the
+    /// new checksum.
+    #[inline]
+    pub fn push(&mut self, byte: u8) -> u32 {
+        let crc = crc32c_append(self.crc, &[byte]);
+
+        if self.is_full() {
+            let out = self.buf[self.pos];
+            self.buf[self.pos] = byte;
+            self.pos = (self.pos + 1) % self.buf.len();
+            self.crc = crc ^ self.table[out as usize];
+        } else {
+            self.buf[self.len] = byte;
+            self.len += 1;
+            self.crc = crc;
+        }
+
+        self.crc
+    }
+
+    /// Get the checksum of the bytes in the window.
+    ///
+    /// Until the window is full, this is the checksum of all the bytes pushed.
+    pub fn crc32c(&self) -> u32 {
+        self.crc
+    }
+
+    /// Empty the window.
+    pub fn reset(&mut self) {
+        self.pos = 0;
+        self.len = 0;
+        self.crc = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    #[test]
+    fn matches_window() {
+        let mut data = vec![0u8; 1000];
+        rand::rng().fill_bytes(&mut data);
+
+        for window in [1, 4, 7, 64, 100] {
+            let mut rolling = Crc32cRolling::new(window);
+
+            for (i, &byte) in data.iter().enumerate() {
+                let start = (i + 1).saturating_sub(window);
+                assert_eq!(rolling.push(byte), crc32c(&data[start..=i]), "{window} {i}");
+                assert_eq!(rolling.is_full(), i + 1 >= window);
+            }
+        }
+    }
+
+    #[test]
+    fn can_reset() {
+        let mut rolling = Crc32cRolling::new(4);
+
+        for &byte in b"abcdefgh" {
+            rolling.push(byte);
+        }
+
+        rolling.reset();
+        assert_eq!(rolling.crc32c(), 0);
+        assert!(!rolling.is_full());
+
+        for &byte in b"xyzabcd" {
+            rolling.push(byte);
+        }
+        assert_eq!(rolling.crc32c(), crc32c(b"abcd"));
+    }
+}
@@ -0,0 +1,76 @@
+//! Provides a precomputed CRC-32C shift operator for a fixed length.
+use crate::hw_tables::CrcTable;
+
+/// Precomputed operator for combining CRC-32C values over a fixed length.
+///
+/// [crc32c_combine](crate::crc32c_combine) rederives the operator for `len2` on every call.
+/// When the second piece always has the same length (e.g. fixed-size chunks), build a
+/// `Crc32cShift` once and reuse it.
+#[derive(Clone)]
+pub struct Crc32cShift {
+    table: CrcTable,
+}
+
+impl Crc32cShift {
+    /// Create the operator for a second piece of `len` bytes.
+    pub fn new(len: usize) -> Self {
+        Self {
+            table: CrcTable::new(len),
+        }
+    }
+
+    /// Shift a CRC past `len` zero bytes.
+    ///
+    /// Equivalent to calling `crc32c_combine(crc, 0, len)`.
+    #[inline]
+    pub fn shift(&self, crc: u32) -> u32 {
+        self.table.shift_u32(crc)
+    }
+
+    /// Computes the "combined" value of two CRC32c values, where the second byte stream is
+    /// `len` bytes long.
+    ///
+    /// Equivalent to calling `crc32c_combine(crc1, crc2, len)`.
+    #[inline]
+    pub fn combine(&self, crc1: u32, crc2: u32) -> u32 {
+        self.shift(crc1) ^ crc2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+    use crate::{crc32c, crc32c_append, crc32c_combine};
+
+    #[test]
+    fn matches_combine() {
+        for &len in &[1, 7, 256, 4096, 8192, 4 << 20] {
+            let shift = Crc32cShift::new(len);
+
+            for _ in 0..16 {
+                let crc1 = rand::rng().next_u32();
+                let crc2 = rand::rng().next_u32();
+
+                assert_eq!(shift.shift(crc1), crc32c_combine(crc1, 0, len));
+                assert_eq!(shift.combine(crc1, crc2), crc32c_combine(crc1, crc2, len));
+            }
+        }
+    }
+
+    #[test]
+    fn can_combine() {
+        let mut a = vec![0u8; 100];
+        let mut b = vec![0u8; 4096];
+        rand::rng().fill_bytes(&mut a);
+        rand::rng().fill_bytes(&mut b);
+
+        let shift = Crc32cShift::new(b.len());
+
+        assert_eq!(
+            shift.combine(crc32c(&a), crc32c(&b)),
+            crc32c_append(crc32c(&a), &b)
+        );
+    }
+}
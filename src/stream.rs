@@ -0,0 +1,269 @@
+//! Provides adapters for [Stream]s and [Sink]s of byte chunks which checksum the chunks passing
+//! through.
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{ready, Context, Poll};
+
+use futures_core::{Stream, TryStream};
+use futures_sink::Sink;
+
+use crate::{ChecksumMismatch, Crc32c};
+
+/// A shared handle to the checksum computed by a [Crc32cStream] or a [Crc32cSink].
+///
+/// The handle stays valid after the adapter is consumed (e.g. by a body type which takes
+/// ownership of the stream), so the checksum can be read once the stream ends.
+#[derive(Debug, Clone, Default)]
+pub struct Crc32cHandle {
+    digest: Arc<Mutex<Crc32c>>,
+}
+
+impl Crc32cHandle {
+    /// Get the checksum of all chunks so far.
+    pub fn crc32c(&self) -> u32 {
+        self.digest().crc()
+    }
+
+    /// Get the checksum of all chunks so far, with their total length.
+    pub fn digest(&self) -> Crc32c {
+        *self.lock()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Crc32c> {
+        self.digest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+type IntoError<E> = fn(ChecksumMismatch) -> E;
+
+/// [Stream] adapter which tracks the checksum of the chunks of a stream of `Result`s.
+///
+/// # Example
+///
+/// ```rust
+/// use futures::{executor, stream, TryStreamExt};
+/// use crc32c::Crc32cStream;
+///
+/// let chunks = vec![Ok::<_, std::io::Error>(b"Hello ".to_vec()), Ok(b"world!".to_vec())];
+/// let stream = Crc32cStream::verified(stream::iter(chunks), 0x7B_98_E7_51);
+/// let handle = stream.handle();
+///
+/// let body: Vec<_> = executor::block_on(stream.try_concat()).unwrap();
+///
+/// assert_eq!(body, b"Hello world!");
+/// assert_eq!(handle.crc32c(), 0x7B_98_E7_51);
+/// ```
+pub struct Crc32cStream<S: TryStream> {
+    inner: S,
+    handle: Crc32cHandle,
+    /// The expected checksum, and the conversion of a mismatch to an error item.
+    verify: Option<(u32, IntoError<S::Error>)>,
+    done: bool,
+}
+
+impl<S: TryStream + Unpin> Crc32cStream<S> {
+    /// Wrap a stream.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            handle: Crc32cHandle::default(),
+            verify: None,
+            done: false,
+        }
+    }
+
+    /// Wrap a stream whose chunks must have the `expected` checksum.
+    ///
+    /// If they do not, an error item with a [ChecksumMismatch] is yielded after the last chunk.
+    pub fn verified(inner: S, expected: u32) -> Self
+    where
+        S::Error: From<ChecksumMismatch>,
+    {
+        Self {
+            verify: Some((expected, S::Error::from)),
+            ..Self::new(inner)
+        }
+    }
+
+    /// Get a handle to the checksum.
+    pub fn handle(&self) -> Crc32cHandle {
+        self.handle.clone()
+    }
+
+    /// Unwrap the inner stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Stream for Crc32cStream<S>
+where
+    S: TryStream + Unpin,
+    S::Ok: AsRef<[u8]>,
+{
+    type Item = Result<S::Ok, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(Pin::new(&mut this.inner).try_poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                this.handle.lock().update(chunk.as_ref());
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => {
+                this.done = true;
+
+                match this.verify {
+                    Some((expected, into_error)) if this.handle.crc32c() != expected => {
+                        let actual = this.handle.crc32c();
+                        Poll::Ready(Some(Err(into_error(ChecksumMismatch { expected, actual }))))
+                    }
+                    _ => Poll::Ready(None),
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+
+        // The mismatch error may add an item.
+        let (lower, upper) = self.inner.size_hint();
+        let extra = usize::from(self.verify.is_some());
+        (lower, upper.and_then(|upper| upper.checked_add(extra)))
+    }
+}
+
+/// [Sink] adapter which tracks the checksum of the chunks sent to a sink.
+///
+/// Only the chunks accepted by the inner sink are checksummed.
+pub struct Crc32cSink<S> {
+    inner: S,
+    handle: Crc32cHandle,
+}
+
+impl<S: Unpin> Crc32cSink<S> {
+    /// Wrap a sink.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            handle: Crc32cHandle::default(),
+        }
+    }
+
+    /// Get a handle to the checksum.
+    pub fn handle(&self) -> Crc32cHandle {
+        self.handle.clone()
+    }
+
+    /// Unwrap the inner sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, B> Sink<B> for Crc32cSink<S>
+where
+    S: Sink<B> + Unpin,
+    B: AsRef<[u8]>,
+{
+    type Error = S::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: B) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+
+        // The chunk is only accounted for once the inner sink accepts it.
+        let mut digest = this.handle.lock();
+        let mut updated = *digest;
+        updated.update(item.as_ref());
+
+        Pin::new(&mut this.inner).start_send(item)?;
+
+        *digest = updated;
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use futures::executor::block_on;
+    use futures::{stream, SinkExt, StreamExt, TryStreamExt};
+
+    use super::*;
+
+    const TEST_STRING: &[u8] =
+        b"This is a very long string which is used to test the CRC-32-Castagnoli function.";
+    const CHECKSUM: u32 = 0x20_CB_1E_59;
+
+    fn chunks() -> impl TryStream<Ok = Vec<u8>, Error = io::Error> + Unpin {
+        stream::iter(TEST_STRING.chunks(7).map(|chunk| Ok(chunk.to_vec())))
+    }
+
+    #[test]
+    fn can_stream() {
+        let stream = Crc32cStream::new(chunks());
+        let handle = stream.handle();
+
+        let body = block_on(stream.try_concat()).unwrap();
+        assert_eq!(body, TEST_STRING);
+        assert_eq!(handle.crc32c(), CHECKSUM);
+        assert_eq!(handle.digest(), Crc32c::new(TEST_STRING));
+    }
+
+    #[test]
+    fn can_verify() {
+        let stream = Crc32cStream::verified(chunks(), CHECKSUM);
+        assert_eq!(block_on(stream.try_concat()).unwrap(), TEST_STRING);
+
+        let stream = Crc32cStream::verified(chunks(), CHECKSUM ^ 1);
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(items.len(), TEST_STRING.chunks(7).len() + 1);
+
+        let err = items.into_iter().last().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mismatch = err.into_inner().unwrap().downcast::<ChecksumMismatch>();
+        assert_eq!(
+            *mismatch.unwrap(),
+            ChecksumMismatch {
+                expected: CHECKSUM ^ 1,
+                actual: CHECKSUM
+            }
+        );
+    }
+
+    #[test]
+    fn can_sink() {
+        let mut sink = Crc32cSink::new(Vec::<Vec<u8>>::new());
+        let handle = sink.handle();
+
+        for chunk in TEST_STRING.chunks(7) {
+            block_on(sink.send(chunk.to_vec())).unwrap();
+        }
+
+        assert_eq!(handle.crc32c(), CHECKSUM);
+        assert_eq!(sink.into_inner().concat(), TEST_STRING);
+    }
+}
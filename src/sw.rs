@@ -1,11 +1,18 @@
 //! Implements crc32c without hardware support.
 
+use crate::poly::POLYNOMIAL;
+use crate::tables;
 use crate::util::{self, U64Le};
 
 /// 8-KiB lookup table.
 pub struct CrcTable([[u32; 256]; 8]);
 
 impl CrcTable {
+    /// Builds the table for a reflected CRC-32 `polynomial`.
+    pub fn new(polynomial: u32) -> Self {
+        Self(tables::sw_table(polynomial))
+    }
+
     /// Returns an entry from the table.
     #[inline]
     pub fn at(&self, i: u8, j: u8) -> u64 {
@@ -17,43 +24,70 @@ impl CrcTable {
 
 const CRC_TABLE: CrcTable = CrcTable(include!(concat!(env!("OUT_DIR"), "/", "sw.table")));
 
-/// Software implementation of the algorithm.
-pub fn crc32c(crci: u32, buffer: &[u8]) -> u32 {
-    let mut crc = u64::from(!crci);
+/// Software implementation of the algorithm, on the raw CRC register.
+pub fn crc32c_raw(crc: u32, buffer: &[u8]) -> u32 {
+    update::<false>(&CRC_TABLE, crc, buffer)
+}
+
+/// Updates a reflected CRC register with the bytes of the buffer.
+///
+/// With `REVERSE`, the bits of each byte are reversed first. This computes a non-reflected CRC,
+/// in a bit-reversed register.
+pub fn update<const REVERSE: bool>(table: &CrcTable, crc: u32, buffer: &[u8]) -> u32 {
+    let mut crc = u64::from(crc);
 
     let (start, mid, end) = util::split(buffer);
 
-    crc = crc_u8(crc, start);
+    crc = crc_u8::<REVERSE>(table, crc, start);
 
-    crc = crc_u64(crc, mid);
+    crc = crc_u64::<REVERSE>(table, crc, mid);
 
-    crc = crc_u8(crc, end);
+    crc = crc_u8::<REVERSE>(table, crc, end);
 
-    !(crc as u32)
+    crc as u32
+}
+
+/// Feeds the low `bits` bits of `byte` into the CRC register, least significant bit first.
+pub fn crc_bits(crc: u32, byte: u8, bits: u32) -> u32 {
+    (0..bits).fold(crc, |crc, i| {
+        let crc = crc ^ u32::from((byte >> i) & 1);
+
+        if crc & 1 == 0 {
+            crc >> 1
+        } else {
+            (crc >> 1) ^ POLYNOMIAL
+        }
+    })
 }
 
 #[inline]
-fn crc_u8(crc: u64, buffer: &[u8]) -> u64 {
+fn crc_u8<const REVERSE: bool>(table: &CrcTable, crc: u64, buffer: &[u8]) -> u64 {
     buffer.iter().fold(crc, |crc, &next| {
+        let next = if REVERSE { next.reverse_bits() } else { next };
         let index = (crc ^ u64::from(next)) as u8;
-        CRC_TABLE.at(0, index) ^ (crc >> 8)
+        table.at(0, index) ^ (crc >> 8)
     })
 }
 
 #[inline]
-fn crc_u64(crci: u64, buffer: &[U64Le]) -> u64 {
+fn crc_u64<const REVERSE: bool>(table: &CrcTable, crci: u64, buffer: &[U64Le]) -> u64 {
     buffer.iter().fold(crci, |crc, &next| {
-        let crc = crc ^ next.get();
+        let next = if REVERSE {
+            next.get().reverse_bits().swap_bytes()
+        } else {
+            next.get()
+        };
+        let crc = crc ^ next;
 
         // Note: I've tried refactoring this to a for-loop,
         // but then it gets worse performance.
-        CRC_TABLE.at(7, crc as u8)
-            ^ CRC_TABLE.at(6, (crc >> 8) as u8)
-            ^ CRC_TABLE.at(5, (crc >> 16) as u8)
-            ^ CRC_TABLE.at(4, (crc >> 24) as u8)
-            ^ CRC_TABLE.at(3, (crc >> 32) as u8)
-            ^ CRC_TABLE.at(2, (crc >> 40) as u8)
-            ^ CRC_TABLE.at(1, (crc >> 48) as u8)
-            ^ CRC_TABLE.at(0, (crc >> 56) as u8)
+        table.at(7, crc as u8)
+            ^ table.at(6, (crc >> 8) as u8)
+            ^ table.at(5, (crc >> 16) as u8)
+            ^ table.at(4, (crc >> 24) as u8)
+            ^ table.at(3, (crc >> 32) as u8)
+            ^ table.at(2, (crc >> 40) as u8)
+            ^ table.at(1, (crc >> 48) as u8)
+            ^ table.at(0, (crc >> 56) as u8)
     })
 }
@@ -0,0 +1,57 @@
+//! Generates the lookup tables for a reflected CRC-32 polynomial.
+//!
+//! This file is also included by build.rs, to generate the CRC-32C tables at compile time.
+
+use crate::poly;
+
+/// Table for a quadword-at-a-time software CRC.
+pub fn sw_table(polynomial: u32) -> [[u32; 256]; 8] {
+    let mut table: [[u32; 256]; 8] = [[0u32; 256]; 8];
+
+    for n in 0..256_u32 {
+        let mut crc = n;
+
+        for _ in 0..8 {
+            if crc % 2 == 0 {
+                crc /= 2;
+            } else {
+                crc /= 2;
+                crc ^= polynomial;
+            }
+        }
+
+        table[0][n as usize] = crc;
+    }
+
+    for n in 0..256 {
+        let mut crc = table[0][n as usize];
+        for k in 1..8 {
+            crc = table[0][(crc as u8) as usize] ^ (crc >> 8);
+            table[k as usize][n as usize] = crc;
+        }
+    }
+
+    table
+}
+
+/// Table for shifting a CRC past `len` zero bytes, one byte of the register at a time.
+pub fn hw_table(polynomial: u32, len: usize) -> [[u32; 256]; 4] {
+    let mut zeroes: [[u32; 256]; 4] = [[0u32; 256]; 4];
+
+    let x = poly::Poly32::X.to_register();
+    let op = poly::pow_mod(poly::pow_mod(x, len as u64, polynomial), 8, polynomial);
+
+    for (i, row) in zeroes.iter_mut().enumerate() {
+        for bit in 0..8 {
+            // The operator is linear, so each entry is the XOR of the columns for its set bits.
+            let column = poly::multiply_mod(op, 1 << (i * 8 + bit), polynomial);
+            let step = 1 << bit;
+
+            for n in step..(step * 2) {
+                row[n] = row[n - step] ^ column;
+            }
+        }
+    }
+
+    zeroes
+}
@@ -0,0 +1,162 @@
+//! Detects the CRC-32C convention used to produce a stored checksum.
+use crate::{crc32c, crc32c_append, crc32c_raw_update};
+
+/// Constant added by LevelDB's (and Snappy's) masking of CRCs.
+const MASK_DELTA: u32 = 0xA2_82_EA_D8;
+
+/// A convention for computing and storing a CRC-32C.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// The standard CRC-32C, as computed by [crc32c].
+    Standard,
+    /// The CRC register without inversion on entry or exit (zero seed, no final XOR).
+    Raw,
+    /// The CRC register seeded with all ones and without final XOR, i.e. `!crc32c(data)`, as
+    /// computed by the Linux kernel's `crc32c(~0, ...)` for ext4 and btrfs.
+    Kernel,
+    /// The standard CRC-32C, masked as in LevelDB and Snappy: rotated right by 15 bits, plus
+    /// `0xa282ead8`.
+    Masked,
+    /// The standard CRC-32C, stored in the opposite byte order.
+    ByteSwapped,
+    /// Computed as `crc32c_append(0xFFFFFFFF, data)`, as happens when an all-ones seed is passed
+    /// to an API which already inverts it.
+    SeededOnes,
+    /// The standard CRC-32C of the data with the 4-byte field at `offset` zeroed, as used by
+    /// formats which store the checksum inside the block it covers.
+    ZeroedField {
+        /// Offset of the checksum field in the data.
+        offset: usize,
+        /// Byte order in which the checksum is stored in the field.
+        order: ByteOrder,
+    },
+}
+
+/// The byte order of a stored checksum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Least significant byte first.
+    LittleEndian,
+    /// Most significant byte first.
+    BigEndian,
+}
+
+impl ByteOrder {
+    /// Returns the bytes of `value` in this order.
+    pub fn to_bytes(self, value: u32) -> [u8; 4] {
+        match self {
+            ByteOrder::LittleEndian => value.to_le_bytes(),
+            ByteOrder::BigEndian => value.to_be_bytes(),
+        }
+    }
+}
+
+impl Variant {
+    /// Computes the checksum of `data` according to this convention.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [Variant::ZeroedField] does not fit in `data`.
+    pub fn compute(&self, data: &[u8]) -> u32 {
+        match *self {
+            Variant::Standard => crc32c(data),
+            Variant::Raw => crc32c_raw_update(0, data),
+            Variant::Kernel => crc32c_raw_update(!0, data),
+            Variant::Masked => crc32c(data).rotate_right(15).wrapping_add(MASK_DELTA),
+            Variant::ByteSwapped => crc32c(data).swap_bytes(),
+            Variant::SeededOnes => crc32c_append(u32::MAX, data),
+            Variant::ZeroedField { offset, .. } => {
+                let (head, field) = data.split_at(offset);
+                let tail = &field[4..];
+
+                let crc = crc32c(head);
+                let crc = crc32c_append(crc, &[0; 4]);
+                crc32c_append(crc, tail)
+            }
+        }
+    }
+}
+
+/// Returns every convention for which `stored` is the checksum of `data`.
+///
+/// [Variant::ZeroedField] is tried at each offset where `stored` appears in `data`, in either
+/// byte order, which is reported with the variant.
+pub fn detect_variant(data: &[u8], stored: u32) -> Vec<Variant> {
+    let fixed = [
+        Variant::Standard,
+        Variant::Raw,
+        Variant::Kernel,
+        Variant::Masked,
+        Variant::ByteSwapped,
+        Variant::SeededOnes,
+    ];
+
+    let fields = data.windows(4).enumerate().flat_map(|(offset, field)| {
+        let matching = move |order: ByteOrder| {
+            (field == order.to_bytes(stored)).then_some(Variant::ZeroedField { offset, order })
+        };
+
+        matching(ByteOrder::LittleEndian)
+            .into_iter()
+            .chain(matching(ByteOrder::BigEndian))
+    });
+
+    fixed
+        .iter()
+        .copied()
+        .chain(fields)
+        .filter(|variant| variant.compute(data) == stored)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_STRING: &[u8] =
+        b"This is a very long string which is used to test the CRC-32-Castagnoli function.";
+    const CHECKSUM: u32 = 0x20_CB_1E_59;
+
+    #[test]
+    fn can_compute() {
+        assert_eq!(Variant::Standard.compute(TEST_STRING), CHECKSUM);
+        assert_eq!(Variant::ByteSwapped.compute(TEST_STRING), 0x59_1E_CB_20);
+        assert_eq!(Variant::Kernel.compute(TEST_STRING), !CHECKSUM);
+
+        // LevelDB's crc32c::Unmask recovers the standard CRC.
+        let masked = Variant::Masked.compute(TEST_STRING);
+        let unmasked = masked.wrapping_sub(MASK_DELTA).rotate_left(15);
+        assert_eq!(unmasked, CHECKSUM);
+    }
+
+    #[test]
+    fn can_detect() {
+        for variant in [
+            Variant::Standard,
+            Variant::Raw,
+            Variant::Kernel,
+            Variant::Masked,
+            Variant::ByteSwapped,
+            Variant::SeededOnes,
+        ] {
+            let stored = variant.compute(TEST_STRING);
+            assert_eq!(detect_variant(TEST_STRING, stored), vec![variant]);
+        }
+
+        assert!(detect_variant(TEST_STRING, 0).is_empty());
+    }
+
+    #[test]
+    fn can_detect_field() {
+        for order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            let mut block = TEST_STRING.to_vec();
+            let field = Variant::ZeroedField { offset: 8, order };
+
+            block[8..12].copy_from_slice(&[0; 4]);
+            let stored = field.compute(&block);
+            block[8..12].copy_from_slice(&order.to_bytes(stored));
+
+            assert_eq!(detect_variant(&block, stored), vec![field]);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Computes the CRC of data split across several buffers.
+//!
+//! The hardware implementations hash the 8-byte aligned words of a buffer (see `util::split`),
+//! and the bytes before and after them one at a time. Hashing many small fragments separately
+//! would thus mostly use the slow path, so small fragments are first gathered in an aligned
+//! buffer. Large fragments are hashed in place from their first aligned word: the bytes before
+//! it are gathered after the previous fragments, and the bytes after their last aligned word
+//! are carried over to the next ones, so that the words split by the boundaries are stitched
+//! together.
+
+use std::cmp;
+
+use crate::crc32c_raw_update;
+
+/// Size of the buffer in which small fragments are gathered.
+const STITCH: usize = 256;
+
+/// A buffer aligned on 8 bytes, so that the hardware implementations hash it by words.
+#[repr(align(8))]
+struct Aligned([u8; STITCH]);
+
+/// Updates a raw CRC register with a sequence of buffers.
+pub(crate) struct Stitcher {
+    crc: u32,
+    buf: Aligned,
+    len: usize,
+}
+
+impl Stitcher {
+    /// Starts from the raw CRC register `crc`.
+    pub(crate) fn new(crc: u32) -> Self {
+        Self {
+            crc,
+            buf: Aligned([0; STITCH]),
+            len: 0,
+        }
+    }
+
+    /// Appends the next fragment.
+    pub(crate) fn update(&mut self, mut data: &[u8]) {
+        if data.len() < STITCH {
+            while !data.is_empty() {
+                data = self.gather(data, STITCH - self.len);
+                if self.len == STITCH {
+                    self.flush();
+                }
+            }
+            return;
+        }
+
+        // Complete the pending bytes with the head of the fragment, up to its first aligned word.
+        let head = data.as_ptr().align_offset(8);
+        let head = if head < 8 { head } else { 0 };
+        if self.len + head > STITCH {
+            self.flush();
+        }
+        data = self.gather(data, head);
+        self.flush();
+
+        let (words, tail) = data.split_at(data.len() - data.len() % 8);
+        self.crc = crc32c_raw_update(self.crc, words);
+        self.gather(tail, tail.len());
+    }
+
+    /// Returns the raw CRC register after all fragments.
+    pub(crate) fn finish(mut self) -> u32 {
+        self.flush();
+        self.crc
+    }
+
+    /// Copies up to `max` bytes of `data` in the buffer, and returns the rest.
+    fn gather<'a>(&mut self, data: &'a [u8], max: usize) -> &'a [u8] {
+        let (head, rest) = data.split_at(cmp::min(max, data.len()));
+
+        self.buf.0[self.len..self.len + head.len()].copy_from_slice(head);
+        self.len += head.len();
+        rest
+    }
+
+    fn flush(&mut self) {
+        self.crc = crc32c_raw_update(self.crc, &self.buf.0[..self.len]);
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, RngCore};
+
+    use super::*;
+    use crate::crc32c;
+
+    #[test]
+    fn matches_contiguous() {
+        let mut rng = rand::rng();
+        let mut data = vec![0; 20_000];
+        rng.fill_bytes(&mut data);
+
+        for max in [1, 9, 300, 5000] {
+            let mut stitcher = Stitcher::new(!0);
+            let mut rest = &data[..];
+
+            while !rest.is_empty() {
+                let (fragment, tail) = rest.split_at(rng.random_range(0..=max).min(rest.len()));
+                stitcher.update(fragment);
+                rest = tail;
+            }
+
+            assert_eq!(!stitcher.finish(), crc32c(&data));
+        }
+    }
+
+    #[test]
+    fn stitches_large_fragments() {
+        let mut rng = rand::rng();
+        let mut data = vec![0; 20_000];
+        rng.fill_bytes(&mut data);
+
+        for start in 0..8 {
+            let mut stitcher = Stitcher::new(!0);
+            stitcher.update(&data[..start]);
+            let mut rest = &data[start..];
+
+            while !rest.is_empty() {
+                let len = rng.random_range(STITCH..2 * STITCH).min(rest.len());
+                let (fragment, tail) = rest.split_at(len);
+                stitcher.update(fragment);
+                rest = tail;
+
+                // Only the bytes after the last aligned word of a large fragment are pending.
+                if fragment.len() >= STITCH {
+                    assert!(stitcher.len < 8, "{}", stitcher.len);
+                }
+            }
+
+            assert_eq!(!stitcher.finish(), crc32c(&data));
+        }
+    }
+}
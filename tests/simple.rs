@@ -1,7 +1,11 @@
 extern crate crc32c;
 extern crate rand;
 
-use crc32c::{crc32c, crc32c_append, crc32c_combine};
+use crc32c::{
+    crc32c, crc32c_append, crc32c_append_bits, crc32c_append_repeat, crc32c_combine,
+    crc32c_combine_bits, crc32c_deque, crc32c_raw_combine, crc32c_raw_update, crc32c_repeat,
+    crc32c_vectored,
+};
 use rand::RngCore;
 
 #[test]
@@ -62,3 +66,147 @@ fn very_big() {
 
     assert_eq!(0x12_BD_91_91, crc32c::crc32c(buf.as_bytes()));
 }
+
+#[test]
+fn crc_raw() {
+    let v = crc32c_raw_update(!0, b"012345678910");
+    assert_eq!(!0x84_12_E2_81, v);
+
+    let v = crc32c_raw_update(!0, b"01234");
+    let v = crc32c_raw_update(v, b"5678910");
+    assert_eq!(!0x84_12_E2_81, v);
+
+    assert_eq!(crc32c_raw_update(0, &[0; 100]), 0);
+}
+
+#[test]
+fn crc_raw_combine() {
+    let mut a_buf = vec![0u8; 100];
+    let mut b_buf = vec![0u8; 37];
+    rand::rng().fill_bytes(&mut a_buf);
+    rand::rng().fill_bytes(&mut b_buf);
+
+    for seed in [0, !0, 0x1234_5678] {
+        let a = crc32c_raw_update(seed, &a_buf);
+        let b = crc32c_raw_update(0, &b_buf);
+
+        assert_eq!(
+            crc32c_raw_combine(a, b, b_buf.len()),
+            crc32c_raw_update(a, &b_buf)
+        );
+    }
+}
+
+#[test]
+fn crc_repeat() {
+    for pattern_length in [0, 1, 3, 8, 100] {
+        let mut pattern = vec![0u8; pattern_length];
+        rand::rng().fill_bytes(&mut pattern);
+
+        for times in 0..40 {
+            let expanded = pattern.repeat(times);
+
+            assert_eq!(crc32c_repeat(&pattern, times as u64), crc32c(&expanded));
+            assert_eq!(
+                crc32c_append_repeat(0x1234_5678, &pattern, times as u64),
+                crc32c_append(0x1234_5678, &expanded)
+            );
+        }
+    }
+}
+
+// Tests a 4-KiB block repeated 4096 times.
+#[test]
+fn crc_repeat_big() {
+    let mut pattern = vec![0u8; 4096];
+    rand::rng().fill_bytes(&mut pattern);
+
+    let expanded = pattern.repeat(4096);
+
+    assert_eq!(crc32c_repeat(&pattern, 4096), crc32c(&expanded));
+}
+
+// Bit-at-a-time reference implementation, consuming the low-order bits of each byte first.
+fn crc_bits_reference(crc: u32, data: &[u8], bit_len: usize) -> u32 {
+    let mut crc = !crc;
+
+    for i in 0..bit_len {
+        let bit = (data[i / 8] >> (i % 8)) & 1;
+        crc ^= u32::from(bit);
+        crc = if crc & 1 == 0 {
+            crc >> 1
+        } else {
+            (crc >> 1) ^ 0x82_F6_3B_78
+        };
+    }
+
+    !crc
+}
+
+#[test]
+fn crc_bits() {
+    let mut buf = vec![0u8; 24];
+    rand::rng().fill_bytes(&mut buf);
+
+    for bit_len in 0..=buf.len() * 8 {
+        assert_eq!(
+            crc_bits_reference(0, &buf, bit_len),
+            crc32c_append_bits(0, &buf, bit_len)
+        );
+    }
+
+    assert_eq!(crc32c_append_bits(0, &buf, buf.len() * 8), crc32c(&buf));
+}
+
+#[test]
+fn crc_combine_bits() {
+    let mut a_buf = vec![0u8; 5];
+    let mut b_buf = vec![0u8; 5];
+    rand::rng().fill_bytes(&mut a_buf);
+    rand::rng().fill_bytes(&mut b_buf);
+
+    for a_bits in 0..=a_buf.len() * 8 {
+        for b_bits in 0..=b_buf.len() * 8 {
+            let a = crc32c_append_bits(0, &a_buf, a_bits);
+            let b = crc32c_append_bits(0, &b_buf, b_bits);
+
+            let bits: Vec<bool> = (0..a_bits)
+                .map(|i| (a_buf[i / 8] >> (i % 8)) & 1 == 1)
+                .chain((0..b_bits).map(|i| (b_buf[i / 8] >> (i % 8)) & 1 == 1))
+                .collect();
+            let mut ab_buf = vec![0u8; bits.len().div_ceil(8)];
+            for (i, &bit) in bits.iter().enumerate() {
+                ab_buf[i / 8] |= u8::from(bit) << (i % 8);
+            }
+
+            assert_eq!(
+                crc32c_combine_bits(a, b, b_bits as u64),
+                crc32c_append_bits(0, &ab_buf, bits.len())
+            );
+        }
+    }
+}
+
+#[test]
+fn crc_vectored() {
+    let mut buf = vec![0u8; 5000];
+    rand::rng().fill_bytes(&mut buf);
+
+    let (a, rest) = buf.split_at(3);
+    let (b, rest) = rest.split_at(1000);
+    let (c, d) = rest.split_at(11);
+    let slices = [a, b, c, &[], d];
+    assert_eq!(crc32c_vectored(&slices), crc32c(&buf));
+
+    let io_slices: Vec<_> = slices.iter().map(|s| std::io::IoSlice::new(s)).collect();
+    assert_eq!(crc32c_vectored(&io_slices), crc32c(&buf));
+
+    // Fill a ring buffer, then make it wrap around.
+    let mut deque = std::collections::VecDeque::with_capacity(1000);
+    let cap = deque.capacity();
+    deque.extend(&buf[..cap]);
+    deque.drain(..cap / 2);
+    deque.extend(&buf[cap..cap + cap / 2]);
+    assert!(!deque.as_slices().1.is_empty());
+    assert_eq!(crc32c_deque(&deque), crc32c(&buf[cap / 2..cap + cap / 2]));
+}